@@ -0,0 +1,214 @@
+use std::f32::consts::PI;
+
+use bevy::math::vec2;
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use bevy::utils::Duration;
+use rand::Rng;
+
+use crate::castle::Castle;
+use crate::damage::Health;
+use crate::enemy::EnemyType;
+use crate::player::Player;
+use crate::state::playing;
+use crate::*;
+
+pub struct AiPlugin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiState {
+    Wander,
+    ChaseCastle,
+    ChasePlayer,
+    Attack,
+    Flee,
+}
+
+#[derive(Component)]
+pub struct EnemyAi {
+    pub state: AiState,
+    /// Whether `Attack` targets the player rather than the castle.
+    pub attacking_player: bool,
+    pub wander_target: Vec2,
+    has_fled: bool,
+    timer: Timer,
+}
+
+#[derive(Event)]
+pub struct EnemyShotEvent(pub Entity);
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<EnemyShotEvent>().add_systems(
+            Update,
+            (
+                handle_enemy_shot_events,
+                update_enemy_ai.run_if(on_timer(Duration::from_secs_f32(ENEMY_AI_TICK_SECS))),
+            )
+                .chain()
+                .run_if(playing),
+        );
+    }
+}
+
+impl EnemyAi {
+    /// Newly spawned enemies mill around briefly before committing to a target.
+    pub fn new(pos: Vec2) -> Self {
+        let mut rng = rand::thread_rng();
+        let wander_secs = rng.gen_range(ENEMY_WANDER_TIME_SECS / 2.0..ENEMY_WANDER_TIME_SECS);
+
+        Self {
+            state: AiState::Wander,
+            attacking_player: false,
+            wander_target: random_point_near(pos),
+            has_fled: false,
+            timer: Timer::from_seconds(wander_secs, TimerMode::Once),
+        }
+    }
+
+    /// Where this enemy wants to go, or `None` when it should follow the castle flow field.
+    pub fn target(&self, pos: Vec2, player_pos: Vec2, castle_pos: Vec2) -> Option<Vec2> {
+        match self.state {
+            AiState::Wander => Some(self.wander_target),
+            AiState::ChaseCastle => None,
+            AiState::ChasePlayer => Some(player_pos),
+            AiState::Attack if self.attacking_player => Some(player_pos),
+            AiState::Attack => Some(castle_pos),
+            AiState::Flee => Some(pos * 2.0 - player_pos),
+        }
+    }
+
+    pub fn speed_factor(&self) -> f32 {
+        match self.state {
+            AiState::Wander => ENEMY_WANDER_SPEED_FACTOR,
+            AiState::Flee => ENEMY_FLEE_SPEED_FACTOR,
+            _ => 1.0,
+        }
+    }
+
+    fn set_state(&mut self, state: AiState) {
+        self.state = state;
+        match state {
+            AiState::Wander => {
+                self.timer = Timer::from_seconds(ENEMY_WANDER_TIME_SECS, TimerMode::Once)
+            }
+            AiState::Flee => {
+                self.timer = Timer::from_seconds(ENEMY_FLEE_TIME_SECS, TimerMode::Once)
+            }
+            _ => {}
+        }
+    }
+
+    fn attack(&mut self, player: bool) {
+        self.attacking_player = player;
+        self.set_state(AiState::Attack);
+    }
+}
+
+impl EnemyType {
+    fn default_ai_state(&self) -> AiState {
+        match self {
+            EnemyType::Green | EnemyType::Boss => AiState::ChasePlayer,
+            EnemyType::Red | EnemyType::Skin | EnemyType::Ranged => AiState::ChaseCastle,
+        }
+    }
+}
+
+fn handle_enemy_shot_events(
+    mut events: EventReader<EnemyShotEvent>,
+    mut ai_query: Query<&mut EnemyAi>,
+) {
+    for EnemyShotEvent(entity) in events.read() {
+        if let Ok(mut ai) = ai_query.get_mut(*entity) {
+            if !matches!(ai.state, AiState::Flee | AiState::Attack) {
+                ai.set_state(AiState::ChasePlayer);
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_enemy_ai(
+    player_query: Query<&Transform, With<Player>>,
+    castle_query: Query<&Transform, With<Castle>>,
+    mut enemy_query: Query<
+        (&Transform, &Health, &EnemyType, &mut EnemyAi),
+        (Without<Player>, Without<Castle>),
+    >,
+) {
+    let (Ok(player_transform), Ok(castle_transform)) =
+        (player_query.get_single(), castle_query.get_single())
+    else {
+        return;
+    };
+
+    let player_pos = player_transform.translation.truncate();
+    let castle_pos = castle_transform.translation.truncate();
+    let delta = Duration::from_secs_f32(ENEMY_AI_TICK_SECS);
+
+    for (transform, health, enemy_type, mut ai) in enemy_query.iter_mut() {
+        let pos = transform.translation.truncate();
+        let player_distance = pos.distance(player_pos);
+        let castle_distance = pos.distance(castle_pos);
+        ai.timer.tick(delta);
+
+        if !ai.has_fled && health.fraction() < ENEMY_FLEE_HEALTH_FRACTION {
+            ai.has_fled = true;
+            ai.set_state(AiState::Flee);
+            continue;
+        }
+
+        match ai.state {
+            AiState::Wander => {
+                if player_distance < ENEMY_AGGRO_RADIUS {
+                    ai.set_state(AiState::ChasePlayer);
+                } else if ai.timer.finished() {
+                    ai.set_state(enemy_type.default_ai_state());
+                } else if pos.distance(ai.wander_target) < ENEMY_COLLISION_RADIUS {
+                    ai.wander_target = random_point_near(pos);
+                }
+            }
+            AiState::ChaseCastle => {
+                if player_distance < ENEMY_AGGRO_RADIUS {
+                    ai.set_state(AiState::ChasePlayer);
+                } else if castle_distance < enemy_type.attack_range(false) {
+                    ai.attack(false);
+                }
+            }
+            AiState::ChasePlayer => {
+                if player_distance < enemy_type.attack_range(true) {
+                    ai.attack(true);
+                } else if player_distance > ENEMY_DEAGGRO_RADIUS
+                    && enemy_type.default_ai_state() != AiState::ChasePlayer
+                {
+                    ai.set_state(AiState::ChaseCastle);
+                }
+            }
+            AiState::Attack => {
+                let (distance, chase) = if ai.attacking_player {
+                    (player_distance, AiState::ChasePlayer)
+                } else {
+                    (castle_distance, AiState::ChaseCastle)
+                };
+
+                if distance > enemy_type.attack_range(ai.attacking_player) * ENEMY_ATTACK_LEASH {
+                    ai.set_state(chase);
+                }
+            }
+            AiState::Flee => {
+                if ai.timer.finished() {
+                    ai.wander_target = random_point_near(pos);
+                    ai.set_state(AiState::Wander);
+                }
+            }
+        }
+    }
+}
+
+fn random_point_near(pos: Vec2) -> Vec2 {
+    let mut rng = rand::thread_rng();
+    let angle = rng.gen_range(0.0..PI * 2.0);
+    let dist = rng.gen_range(0.0..ENEMY_WANDER_RADIUS);
+
+    pos + vec2(angle.cos(), angle.sin()) * dist
+}
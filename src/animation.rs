@@ -1,9 +1,9 @@
 use bevy::prelude::*;
 
 use crate::{
-    castle::Castle, enemy::{Enemy, EnemyType}, gold::Gold, gui::MenuBG, gun::Gun, player::{self, Player, PlayerState}, CursorPosition
+    castle::Castle, enemy::{Enemy, EnemyType}, gold::Gold, gui::MenuBG, gun::Gun, player::{Player, PlayerState}, CursorPosition
 };
-use crate::state::GameState;
+use crate::state::{playing, GameState};
 
 pub struct AnimationPlugin;
 
@@ -34,7 +34,7 @@ impl Plugin for AnimationPlugin {
                 flip_player_sprite_x,
                 flip_enemy_sprite_x,
             )
-            .run_if(in_state(GameState::InGame)),
+            .run_if(playing),
         );
     }
 }
@@ -88,7 +88,7 @@ fn animate_gold(
 
     for (mut atlas, timer) in gold_query.iter_mut() {
         if timer.just_finished() {
-            atlas.index = 0 + (atlas.index + 1) % 6;
+            atlas.index = (atlas.index + 1) % 6;
         }
     }
 }
@@ -151,11 +151,7 @@ fn flip_player_sprite_x(
 
     let (mut sprite, transform) = player_query.single_mut();
     if let Some(cursor_position) = cursor_position.0 {
-        if cursor_position.x > transform.translation.x {
-            sprite.flip_x = false;
-        } else {
-            sprite.flip_x = true;
-        }
+        sprite.flip_x = cursor_position.x <= transform.translation.x;
     }
 }
 
@@ -169,11 +165,7 @@ fn flip_enemy_sprite_x(
 
     let player_pos = player_query.single().translation;
     for (mut sprite, transform) in enemy_query.iter_mut() {
-        if transform.translation.x < player_pos.x {
-            sprite.flip_x = false;
-        } else {
-            sprite.flip_x = true;
-        }
+        sprite.flip_x = transform.translation.x >= player_pos.x;
     }
 }
 
@@ -187,10 +179,6 @@ fn flip_gun_sprite_y(
 
     let (mut sprite, transform) = gun_query.single_mut();
     if let Some(cursor_position) = cursor_position.0 {
-        if cursor_position.x > transform.translation.x {
-            sprite.flip_y = false;
-        } else {
-            sprite.flip_y = true;
-        }
+        sprite.flip_y = cursor_position.x <= transform.translation.x;
     }
 }
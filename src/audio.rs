@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use bevy::audio::{SpatialScale, Volume};
+use bevy::prelude::*;
+
+use crate::settings::Settings;
+use crate::state::GameState;
+use crate::*;
+
+pub struct GameAudioPlugin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sfx {
+    Shoot,
+    EnemyHit,
+    EnemyDeath,
+    GoldPickup,
+    CastleHit,
+    GameOver,
+    EnemySpawn,
+    PlayerHit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MusicTrack {
+    Menu,
+    Game,
+}
+
+#[derive(Event)]
+pub struct SfxEvent {
+    pub sfx: Sfx,
+    pub position: Option<Vec2>,
+}
+
+#[derive(Component)]
+struct Music(MusicTrack);
+
+#[derive(Component)]
+struct SfxInstance(Sfx);
+
+#[derive(Resource, Default)]
+pub struct GlobalAudio {
+    pub menu_music: Option<Handle<AudioSource>>,
+    pub game_music: Option<Handle<AudioSource>>,
+    pub sfx: HashMap<Sfx, Handle<AudioSource>>,
+}
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SfxEvent>()
+            .insert_resource(GlobalAudio::default())
+            .add_systems(OnEnter(GameState::Loading), load_audio)
+            .add_systems(Update, (update_music, play_sfx, apply_music_volume));
+    }
+}
+
+impl Sfx {
+    fn path(&self) -> &'static str {
+        match self {
+            Sfx::Shoot => SHOOT_SFX_PATH,
+            Sfx::EnemyHit => ENEMY_HIT_SFX_PATH,
+            Sfx::EnemyDeath => ENEMY_DEATH_SFX_PATH,
+            Sfx::GoldPickup => GOLD_PICKUP_SFX_PATH,
+            Sfx::CastleHit => CASTLE_HIT_SFX_PATH,
+            Sfx::GameOver => GAME_OVER_SFX_PATH,
+            Sfx::EnemySpawn => ENEMY_SPAWN_SFX_PATH,
+            Sfx::PlayerHit => PLAYER_HIT_SFX_PATH,
+        }
+    }
+}
+
+impl SfxEvent {
+    pub fn new(sfx: Sfx) -> Self {
+        Self {
+            sfx,
+            position: None,
+        }
+    }
+
+    /// A sound emitted in the world, panned and attenuated relative to the camera.
+    pub fn at(sfx: Sfx, position: Vec2) -> Self {
+        Self {
+            sfx,
+            position: Some(position),
+        }
+    }
+}
+
+fn load_audio(mut audio: ResMut<GlobalAudio>, asset_server: Res<AssetServer>) {
+    audio.menu_music = Some(asset_server.load(MENU_MUSIC_PATH));
+    audio.game_music = Some(asset_server.load(GAME_MUSIC_PATH));
+
+    for sfx in [
+        Sfx::Shoot,
+        Sfx::EnemyHit,
+        Sfx::EnemyDeath,
+        Sfx::GoldPickup,
+        Sfx::CastleHit,
+        Sfx::GameOver,
+        Sfx::EnemySpawn,
+        Sfx::PlayerHit,
+    ] {
+        audio.sfx.insert(sfx, asset_server.load(sfx.path()));
+    }
+}
+
+fn update_music(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    audio: Res<GlobalAudio>,
+    settings: Res<Settings>,
+    music_query: Query<(Entity, &Music)>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+
+    let track = match state.get() {
+        GameState::InGame => MusicTrack::Game,
+        GameState::MainMenu | GameState::Controls | GameState::Settings => MusicTrack::Menu,
+        _ => return,
+    };
+
+    if music_query.iter().any(|(_, music)| music.0 == track) {
+        return;
+    }
+
+    for (entity, _) in music_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let source = match track {
+        MusicTrack::Menu => audio.menu_music.clone(),
+        MusicTrack::Game => audio.game_music.clone(),
+    };
+
+    if let Some(source) = source {
+        commands.spawn((
+            AudioBundle {
+                source,
+                settings: PlaybackSettings::LOOP.with_volume(Volume::new(settings.music_level())),
+            },
+            Music(track),
+        ));
+    }
+}
+
+fn apply_music_volume(settings: Res<Settings>, music_query: Query<&AudioSink, With<Music>>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for sink in music_query.iter() {
+        sink.set_volume(settings.music_level());
+    }
+}
+
+fn play_sfx(
+    mut commands: Commands,
+    mut events: EventReader<SfxEvent>,
+    audio: Res<GlobalAudio>,
+    settings: Res<Settings>,
+    sfx_query: Query<&SfxInstance>,
+) {
+    if events.is_empty() {
+        return;
+    }
+
+    let mut playing: HashMap<Sfx, usize> = HashMap::new();
+    for instance in sfx_query.iter() {
+        *playing.entry(instance.0).or_default() += 1;
+    }
+
+    for event in events.read() {
+        let count = playing.entry(event.sfx).or_default();
+        if *count >= MAX_SIMULTANEOUS_SFX {
+            continue;
+        }
+
+        let Some(source) = audio.sfx.get(&event.sfx) else {
+            continue;
+        };
+
+        *count += 1;
+        let playback = PlaybackSettings::DESPAWN.with_volume(Volume::new(settings.sfx_level()));
+
+        match event.position {
+            Some(position) => commands.spawn((
+                AudioBundle {
+                    source: source.clone(),
+                    settings: playback
+                        .with_spatial(true)
+                        .with_spatial_scale(SpatialScale::new_2d(AUDIO_SPATIAL_SCALE)),
+                },
+                TransformBundle::from_transform(Transform::from_translation(position.extend(0.))),
+                SfxInstance(event.sfx),
+            )),
+            None => commands.spawn((
+                AudioBundle {
+                    source: source.clone(),
+                    settings: playback,
+                },
+                SfxInstance(event.sfx),
+            )),
+        };
+    }
+}
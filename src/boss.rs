@@ -0,0 +1,260 @@
+use std::f32::consts::PI;
+
+use bevy::math::vec2;
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::audio::{Sfx, SfxEvent};
+use crate::damage::Health;
+use crate::enemy::{spawn_enemy, spawn_enemy_bullet, EnemyType};
+use crate::obstacle::Obstacles;
+use crate::player::Player;
+use crate::state::{playing, GameState};
+use crate::world::{random_position_around, GameEntity};
+use crate::*;
+
+pub struct BossPlugin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BossPhase {
+    Summon,
+    Charge,
+    Barrage,
+}
+
+#[derive(Component)]
+pub struct Boss {
+    pub phase: BossPhase,
+    ability: Timer,
+    charge: Option<(Vec2, Timer)>,
+}
+
+#[derive(Resource)]
+struct BossSpawnTimer(Timer);
+
+#[derive(Component)]
+struct BossBar;
+
+#[derive(Component)]
+struct BossBarFill;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BossSpawnTimer(boss_spawn_timer()))
+            .add_systems(
+                OnEnter(GameState::InGame),
+                (reset_boss_timer, spawn_boss_bar),
+            )
+            .add_systems(
+                Update,
+                (
+                    spawn_boss,
+                    update_boss_phase,
+                    use_boss_abilities,
+                    update_boss_bar,
+                )
+                    .run_if(playing),
+            );
+    }
+}
+
+impl Default for Boss {
+    fn default() -> Self {
+        Self {
+            phase: BossPhase::Summon,
+            ability: Timer::from_seconds(BOSS_ABILITY_INTERVAL_SECS, TimerMode::Repeating),
+            charge: None,
+        }
+    }
+}
+
+impl BossPhase {
+    fn from_health(fraction: f32) -> Self {
+        if fraction > 2.0 / 3.0 {
+            BossPhase::Summon
+        } else if fraction > 1.0 / 3.0 {
+            BossPhase::Charge
+        } else {
+            BossPhase::Barrage
+        }
+    }
+}
+
+fn boss_spawn_timer() -> Timer {
+    Timer::from_seconds(BOSS_SPAWN_INTERVAL_SECS, TimerMode::Repeating)
+}
+
+fn reset_boss_timer(mut timer: ResMut<BossSpawnTimer>) {
+    timer.0 = boss_spawn_timer();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_boss(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut timer: ResMut<BossSpawnTimer>,
+    handle: Res<GlobalTextureAtlas>,
+    obstacles: Res<Obstacles>,
+    player_query: Query<&Transform, With<Player>>,
+    boss_query: Query<(), With<Boss>>,
+    mut sfx_events: EventWriter<SfxEvent>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() || !boss_query.is_empty() {
+        return;
+    }
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let pos = random_position_around(
+        player_transform.translation.truncate(),
+        BOSS_SPAWN_DISTANCE,
+        BOSS_SPAWN_DISTANCE,
+    );
+    let pos = obstacles.resolve(pos, BOSS_HIT_RADIUS);
+
+    let boss = spawn_enemy(&mut commands, &handle, EnemyType::Boss, pos, 1.0);
+    commands.entity(boss).insert(Boss::default());
+    sfx_events.send(SfxEvent::at(Sfx::EnemySpawn, pos));
+}
+
+fn update_boss_phase(mut boss_query: Query<(&Health, &mut Boss)>) {
+    for (health, mut boss) in boss_query.iter_mut() {
+        let phase = BossPhase::from_health(health.fraction());
+        if phase != boss.phase {
+            boss.phase = phase;
+            boss.ability.reset();
+        }
+    }
+}
+
+fn use_boss_abilities(
+    mut commands: Commands,
+    time: Res<Time>,
+    handle: Res<GlobalTextureAtlas>,
+    obstacles: Res<Obstacles>,
+    player_query: Query<&Transform, With<Player>>,
+    mut boss_query: Query<(&mut Transform, &mut Boss), Without<Player>>,
+    mut sfx_events: EventWriter<SfxEvent>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation.truncate();
+
+    for (mut transform, mut boss) in boss_query.iter_mut() {
+        let pos = transform.translation.truncate();
+
+        if let Some((dir, timer)) = boss.charge.as_mut() {
+            let pos = obstacles.resolve(pos + *dir * BOSS_CHARGE_SPEED, BOSS_HIT_RADIUS);
+            transform.translation.x = pos.x.clamp(-WORLD_W, WORLD_W);
+            transform.translation.y = pos.y.clamp(-WORLD_H, WORLD_H);
+            if timer.tick(time.delta()).finished() {
+                boss.charge = None;
+            }
+        }
+
+        if !boss.ability.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        match boss.phase {
+            BossPhase::Summon => {
+                let mut rng = rand::thread_rng();
+                for _ in 0..BOSS_SUMMON_COUNT {
+                    let angle = rng.gen_range(0.0..PI * 2.0);
+                    let minion_pos = obstacles.resolve(
+                        pos + vec2(angle.cos(), angle.sin()) * BOSS_SUMMON_RADIUS,
+                        ENEMY_COLLISION_RADIUS,
+                    );
+                    spawn_enemy(&mut commands, &handle, EnemyType::Green, minion_pos, 1.0);
+                }
+                sfx_events.send(SfxEvent::at(Sfx::EnemySpawn, pos));
+            }
+            BossPhase::Charge => {
+                let dir = (player_pos - pos).normalize_or_zero();
+                boss.charge = Some((
+                    dir,
+                    Timer::from_seconds(BOSS_CHARGE_TIME_SECS, TimerMode::Once),
+                ));
+            }
+            BossPhase::Barrage => {
+                for i in 0..BOSS_BARRAGE_BULLETS {
+                    let angle = i as f32 / BOSS_BARRAGE_BULLETS as f32 * PI * 2.0;
+                    let dir = vec2(angle.cos(), angle.sin()).extend(0.);
+                    spawn_enemy_bullet(&mut commands, &handle, transform.translation, dir);
+                }
+                sfx_events.send(SfxEvent::at(Sfx::Shoot, pos));
+            }
+        }
+    }
+}
+
+fn spawn_boss_bar(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(10.0),
+                    left: Val::Percent(25.0),
+                    width: Val::Percent(50.0),
+                    height: Val::Px(BOSS_BAR_HEIGHT),
+                    border: UiRect::all(Val::Px(2.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    display: Display::None,
+                    ..default()
+                },
+                background_color: BackgroundColor::from(Color::BLACK.with_a(0.8)),
+                border_color: BorderColor(Color::WHITE.with_a(0.8)),
+                ..default()
+            },
+            BossBar,
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(0.0),
+                        top: Val::Px(0.0),
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor::from(Color::ORANGE_RED),
+                    ..default()
+                },
+                BossBarFill,
+            ));
+            parent.spawn(TextBundle::from_section(
+                "BOSS",
+                TextStyle {
+                    font: asset_server.load("monogram.ttf"),
+                    font_size: BOSS_BAR_HEIGHT,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+fn update_boss_bar(
+    boss_query: Query<&Health, With<Boss>>,
+    mut bar_query: Query<&mut Style, (With<BossBar>, Without<BossBarFill>)>,
+    mut fill_query: Query<&mut Style, (With<BossBarFill>, Without<BossBar>)>,
+) {
+    let (Ok(mut bar), Ok(mut fill)) = (bar_query.get_single_mut(), fill_query.get_single_mut())
+    else {
+        return;
+    };
+
+    let Some(health) = boss_query.iter().next() else {
+        bar.display = Display::None;
+        return;
+    };
+
+    bar.display = Display::Flex;
+    fill.width = Val::Percent(health.fraction() * 100.0);
+}
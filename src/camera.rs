@@ -1,48 +1,84 @@
-use bevy::{math::vec3, prelude::*};
+use bevy::{math::vec3, prelude::*, render::camera::ScalingMode};
 
-use crate::pan_cam::{PanCam, PanCamPlugin};
+use crate::input::{ActionState, InputAction, Keymap};
+use crate::pan_cam::{PanCam, PanCamPlugin, PanCamSystemSet};
 use crate::player::Player;
-use crate::state::GameState;
+use crate::settings::Settings;
+use crate::state::{playing, GameState};
+use crate::{AUDIO_LISTENER_GAP, WH, WORLD_BORDER_THICKNESS, WORLD_H, WORLD_W};
 
 pub struct FollowCameraPlugin;
 
 impl Plugin for FollowCameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(PanCamPlugin::default())
+        app.add_plugins(PanCamPlugin)
             .add_systems(OnEnter(GameState::Loading), setup_camera)
+            .add_systems(Update, apply_zoom_settings)
             .add_systems(
                 Update,
-                camera_follow_player.run_if(in_state(GameState::InGame)),
+                (
+                    camera_follow_player,
+                    sync_pan_cam_buttons,
+                    clamp_camera_to_world.after(PanCamSystemSet),
+                )
+                    .run_if(playing),
             );
     }
 }
 
-fn setup_camera(mut commands: Commands) {
+fn setup_camera(mut commands: Commands, keymap: Res<Keymap>, settings: Res<Settings>) {
     let mut camera_bundle = Camera2dBundle::default();
-    camera_bundle.projection.scale = 1.5;
+    camera_bundle.projection.scaling_mode = ScalingMode::FixedVertical(WH);
+    camera_bundle.projection.scale = settings.min_zoom;
 
-    commands.spawn(camera_bundle).insert(PanCam {
-        grab_buttons: vec![MouseButton::Right],
+    commands.spawn((camera_bundle, SpatialListener::new(AUDIO_LISTENER_GAP))).insert(PanCam {
+        grab_buttons: keymap.mouse_buttons(InputAction::PanCamera),
         enabled: true,
         zoom_to_cursor: false,
-        min_scale: 1.5,
-        max_scale: Some(2.5),
-        ..default()
+        min_scale: settings.min_zoom,
+        max_scale: Some(settings.max_zoom),
+        min_x: Some(-WORLD_W - WORLD_BORDER_THICKNESS),
+        max_x: Some(WORLD_W + WORLD_BORDER_THICKNESS),
+        min_y: Some(-WORLD_H - WORLD_BORDER_THICKNESS),
+        max_y: Some(WORLD_H + WORLD_BORDER_THICKNESS),
     });
 }
 
+fn apply_zoom_settings(
+    settings: Res<Settings>,
+    mut camera_query: Query<(&mut PanCam, &mut OrthographicProjection)>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (mut pan_cam, mut projection) in camera_query.iter_mut() {
+        pan_cam.min_scale = settings.min_zoom;
+        pan_cam.max_scale = Some(settings.max_zoom);
+        projection.scale = projection.scale.clamp(settings.min_zoom, settings.max_zoom);
+    }
+}
 
+fn sync_pan_cam_buttons(keymap: Res<Keymap>, mut pan_cam_query: Query<&mut PanCam>) {
+    if !keymap.is_changed() {
+        return;
+    }
+
+    for mut pan_cam in pan_cam_query.iter_mut() {
+        pan_cam.grab_buttons = keymap.mouse_buttons(InputAction::PanCamera);
+    }
+}
 
 fn camera_follow_player(
     player_query: Query<&Transform, With<Player>>,
     mut camera_query: Query<&mut Transform, (With<Camera>, Without<Player>)>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
 ) {
     if camera_query.is_empty() || player_query.is_empty() {
         return;
     }
 
-    if keyboard_input.pressed(KeyCode::Space) {
+    if actions.pressed(InputAction::FollowCamera) {
         let mut camera_transform = camera_query.single_mut();
         let player_transform = player_query.single().translation;
         let (x, y) = (player_transform.x, player_transform.y);
@@ -50,3 +86,32 @@ fn camera_follow_player(
         camera_transform.translation = camera_transform.translation.lerp(vec3(x, y, 0.0), 0.01);
     }
 }
+
+/// Keeps the view inside the `PanCam` bounds after moves that bypass it (following, minimap clicks).
+fn clamp_camera_to_world(
+    mut camera_query: Query<(&PanCam, &mut Transform, &OrthographicProjection), With<Camera>>,
+) {
+    for (pan_cam, mut transform, projection) in camera_query.iter_mut() {
+        let (Some(min_x), Some(max_x), Some(min_y), Some(max_y)) =
+            (pan_cam.min_x, pan_cam.max_x, pan_cam.min_y, pan_cam.max_y)
+        else {
+            continue;
+        };
+
+        let half_view = projection.area.size() / 2.0;
+        let x = clamp_axis(transform.translation.x, min_x + half_view.x, max_x - half_view.x);
+        let y = clamp_axis(transform.translation.y, min_y + half_view.y, max_y - half_view.y);
+        if x != transform.translation.x || y != transform.translation.y {
+            transform.translation.x = x;
+            transform.translation.y = y;
+        }
+    }
+}
+
+fn clamp_axis(value: f32, min: f32, max: f32) -> f32 {
+    if min > max {
+        return (min + max) / 2.0;
+    }
+
+    value.clamp(min, max)
+}
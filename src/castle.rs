@@ -1,59 +1,79 @@
 use bevy::prelude::*;
 
 use crate::{
-    animation::AnimationTimer, player::Health, state::GameState, world::GameEntity,
-    GlobalTextureAtlas, CASTLE_HEALTH, CASTLE_SPRITE_SCALE_FACTOR, ENEMY_DAMAGE,
+    animation::AnimationTimer,
+    audio::{Sfx, SfxEvent},
+    damage::{Armor, DamageEvent, DeathEvent, Health, Shield},
+    state::{playing, GameState}, world::GameEntity,
+    GlobalTextureAtlas, CASTLE_ALERT_TIME_SECS, CASTLE_ARMOR, CASTLE_HEALTH, CASTLE_SHIELD,
+    CASTLE_SPRITE_SCALE_FACTOR,
 };
 
-#[derive(Event)]
-pub struct CastleEnemyCollisionEvent;
-
 #[derive(Component)]
 pub struct Castle;
 pub struct CastlePlugin;
 
+/// Runs while the castle has recently been hit; finished otherwise.
+#[derive(Resource)]
+pub struct CastleUnderAttack(pub Timer);
+
+impl Default for CastleUnderAttack {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(CASTLE_ALERT_TIME_SECS, TimerMode::Once);
+        timer.tick(timer.duration());
+        Self(timer)
+    }
+}
+
 impl Plugin for CastlePlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<CastleEnemyCollisionEvent>()
+        app.init_resource::<CastleUnderAttack>()
             .add_systems(OnEnter(GameState::InGame), spawn_castle)
             .add_systems(
                 Update,
-                (handle_castle_enemy_collision_events, handle_castle_death)
-                    .run_if(in_state(GameState::InGame)),
+                (handle_castle_damage_events, handle_castle_death)
+                    .run_if(playing),
             );
     }
 }
 
 fn handle_castle_death(
-    castle_query: Query<&Health, With<Castle>>,
+    castle_query: Query<(), With<Castle>>,
+    mut death_events: EventReader<DeathEvent>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut sfx_events: EventWriter<SfxEvent>,
 ) {
-    if castle_query.is_empty() {
-        return;
-    }
-
-    let health = castle_query.single();
-
-    if health.0 <= 0.0 {
-        next_state.set(GameState::MainMenu);
+    for event in death_events.read() {
+        if castle_query.contains(event.entity) {
+            sfx_events.send(SfxEvent::new(Sfx::GameOver));
+            next_state.set(GameState::MainMenu);
+        }
     }
 }
 
-fn handle_castle_enemy_collision_events(
-    mut castle_query: Query<&mut Health, With<Castle>>,
-    mut events: EventReader<CastleEnemyCollisionEvent>,
+/// Raises the alert and plays the hit sound whenever anything damages the castle.
+fn handle_castle_damage_events(
+    castle_query: Query<(Entity, &Transform), With<Castle>>,
+    mut events: EventReader<DamageEvent>,
+    mut sfx_events: EventWriter<SfxEvent>,
+    mut under_attack: ResMut<CastleUnderAttack>,
+    time: Res<Time>,
 ) {
-    if castle_query.is_empty() {
+    under_attack.0.tick(time.delta());
+    let Ok((castle, transform)) = castle_query.get_single() else {
         return;
-    }
+    };
+
+    let hits = events.read().filter(|event| event.target == castle).count();
 
-    let mut health = castle_query.single_mut();
-    for _ in events.read() {
-        health.0 -= ENEMY_DAMAGE;
+    if hits > 0 {
+        under_attack.0.reset();
+        sfx_events.send(SfxEvent::at(Sfx::CastleHit, transform.translation.truncate()));
     }
 }
 
 fn spawn_castle(mut commands: Commands, handle: Res<GlobalTextureAtlas>) {
+    commands.insert_resource(CastleUnderAttack::default());
     commands.spawn((
         SpriteSheetBundle {
             texture: handle.castle_image.clone().unwrap(),
@@ -65,7 +85,9 @@ fn spawn_castle(mut commands: Commands, handle: Res<GlobalTextureAtlas>) {
             ..default()
         },
         Castle,
-        Health(CASTLE_HEALTH),
+        Health::new(CASTLE_HEALTH),
+        Armor(CASTLE_ARMOR),
+        Shield::new(CASTLE_SHIELD),
         AnimationTimer(Timer::from_seconds(0.15, TimerMode::Repeating)),
         GameEntity,
     ));
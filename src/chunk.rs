@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use bevy::math::{ivec2, vec2, vec3};
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::obstacle::{generate_chunk_obstacles, Obstacle, Obstacles};
+use crate::state::{playing, GameState};
+use crate::world::GameEntity;
+use crate::*;
+
+pub struct ChunkPlugin;
+
+pub const DECORATION_SEED_LAYER: u64 = 1;
+pub const OBSTACLE_SEED_LAYER: u64 = 2;
+
+#[derive(Resource)]
+pub struct WorldSeed(pub u64);
+
+#[derive(Resource, Default)]
+pub struct LoadedChunks(pub HashMap<IVec2, Entity>);
+
+#[derive(Component)]
+pub struct Chunk(pub IVec2);
+
+impl Plugin for ChunkPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WorldSeed(0))
+            .init_resource::<LoadedChunks>()
+            .init_resource::<Obstacles>()
+            .add_systems(OnEnter(GameState::GameInit), reset_chunks)
+            .add_systems(Update, stream_chunks.run_if(playing));
+    }
+}
+
+impl WorldSeed {
+    /// A generator that yields the same values for the same seed, chunk and layer.
+    pub fn chunk_rng(&self, chunk: IVec2, layer: u64) -> StdRng {
+        let chunk_hash = (chunk.x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (chunk.y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+            ^ layer.wrapping_mul(0x1656_67B1_9E37_79F9);
+
+        StdRng::seed_from_u64(self.0 ^ chunk_hash)
+    }
+}
+
+pub fn chunk_at(pos: Vec2) -> IVec2 {
+    (pos / CHUNK_SIZE).floor().as_ivec2()
+}
+
+pub fn chunk_origin(chunk: IVec2) -> Vec2 {
+    chunk.as_vec2() * CHUNK_SIZE
+}
+
+fn is_chunk_in_world(chunk: IVec2) -> bool {
+    let min = chunk_origin(chunk);
+    let max = min + CHUNK_SIZE;
+
+    max.x > -WORLD_W && min.x < WORLD_W && max.y > -WORLD_H && min.y < WORLD_H
+}
+
+fn reset_chunks(mut commands: Commands) {
+    let seed = WorldSeed(rand::thread_rng().gen());
+    info!("World seed: {}", seed.0);
+
+    commands.insert_resource(seed);
+    commands.insert_resource(Obstacles::default());
+    commands.insert_resource(LoadedChunks::default());
+}
+
+fn stream_chunks(
+    mut commands: Commands,
+    mut chunks: ResMut<LoadedChunks>,
+    seed: Res<WorldSeed>,
+    mut obstacles: ResMut<Obstacles>,
+    handle: Res<GlobalTextureAtlas>,
+    camera_query: Query<&Transform, With<Camera>>,
+) {
+    let Ok(camera_transform) = camera_query.get_single() else {
+        return;
+    };
+
+    let center = chunk_at(camera_transform.translation.truncate());
+
+    chunks.0.retain(|chunk, entity| {
+        let keep = (*chunk - center).abs().max_element() <= CHUNK_UNLOAD_RADIUS;
+        if !keep {
+            commands.entity(*entity).despawn_recursive();
+            obstacles.remove_chunk(*chunk);
+        }
+        keep
+    });
+
+    for y in -CHUNK_LOAD_RADIUS..=CHUNK_LOAD_RADIUS {
+        for x in -CHUNK_LOAD_RADIUS..=CHUNK_LOAD_RADIUS {
+            let chunk = center + ivec2(x, y);
+            if chunks.0.contains_key(&chunk) || !is_chunk_in_world(chunk) {
+                continue;
+            }
+
+            let rocks = generate_chunk_obstacles(&seed, chunk);
+            let entity = spawn_chunk(&mut commands, &seed, &rocks, &handle, chunk);
+            chunks.0.insert(chunk, entity);
+            obstacles.insert_chunk(chunk, rocks);
+        }
+    }
+}
+
+fn spawn_chunk(
+    commands: &mut Commands,
+    seed: &WorldSeed,
+    rocks: &[Obstacle],
+    handle: &GlobalTextureAtlas,
+    chunk: IVec2,
+) -> Entity {
+    let mut rng = seed.chunk_rng(chunk, DECORATION_SEED_LAYER);
+    let origin = chunk_origin(chunk);
+
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_translation(origin.extend(0.))),
+            Chunk(chunk),
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            for _ in 0..DECORATIONS_PER_CHUNK {
+                let x = rng.gen_range(0.0..CHUNK_SIZE);
+                let y = rng.gen_range(0.0..CHUNK_SIZE);
+                let pos = origin + vec2(x, y);
+                if pos.x.abs() > WORLD_W || pos.y.abs() > WORLD_H {
+                    continue;
+                }
+
+                parent.spawn(SpriteSheetBundle {
+                    texture: handle.image.clone().unwrap(),
+                    atlas: TextureAtlas {
+                        layout: handle.layout.clone().unwrap(),
+                        index: rng.gen_range(24..=25),
+                    },
+                    transform: Transform::from_translation(vec3(x, y, -1.))
+                        .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+                    ..default()
+                });
+            }
+
+            for rock in rocks {
+                let pos = rock.pos - origin;
+                parent.spawn(SpriteSheetBundle {
+                    texture: handle.rock_image.clone().unwrap(),
+                    atlas: TextureAtlas {
+                        layout: handle.rock_layout.clone().unwrap(),
+                        index: rock.sprite_index,
+                    },
+                    transform: Transform::from_translation(vec3(pos.x, pos.y, 0.5))
+                        .with_scale(Vec3::splat(ROCK_SPRITE_SCALE_FACTOR)),
+                    ..default()
+                });
+            }
+        })
+        .id()
+}
@@ -1,20 +1,26 @@
 use bevy::utils::Duration;
 
 use bevy::{prelude::*, time::common_conditions::on_timer};
-use castle::{Castle, CastleEnemyCollisionEvent};
+use castle::Castle;
 use gold::{Gold, PlayerGoldCollisionEvent};
 use kd_tree::{KdPoint, KdTree};
 
-use crate::player::{Player, PlayerEnemyCollisionEvent};
+use crate::ai::EnemyShotEvent;
+use crate::audio::{Sfx, SfxEvent};
+use crate::difficulty::DifficultyScaling;
+use crate::damage::{Damage, DamageEvent};
+use crate::obstacle::Obstacles;
+use crate::player::Player;
+use crate::status::{ApplyStatusEvent, OnHitStatus};
 use crate::*;
-use crate::{enemy::Enemy, gun::Bullet, state::GameState};
+use crate::{enemy::{Enemy, EnemyType}, gun::{Bullet, Faction}, state::playing};
 
 pub struct CollisionPlugin;
 
 #[derive(Component)]
-struct Collidable {
-    pos: Vec2,
-    entity: Entity,
+pub struct Collidable {
+    pub pos: Vec2,
+    pub entity: Entity,
 }
 
 impl KdPoint for Collidable {
@@ -30,7 +36,7 @@ impl KdPoint for Collidable {
   }  
 
 #[derive(Resource)]
-struct EnemyKdTree(KdTree<Collidable>);
+pub struct EnemyKdTree(pub KdTree<Collidable>);
 
 impl Default for EnemyKdTree {
       fn default() -> Self {
@@ -39,7 +45,7 @@ impl Default for EnemyKdTree {
 }
 
 #[derive(Resource)]
-struct GoldKdTree(KdTree<Collidable>);
+pub struct GoldKdTree(pub KdTree<Collidable>);
 
 impl Default for GoldKdTree {
       fn default() -> Self {
@@ -55,6 +61,8 @@ impl Plugin for CollisionPlugin {
             Update,
             (
                 handle_enemy_bullet_collision,
+                handle_bullet_obstacle_collision,
+                handle_enemy_projectile_collision,
                 (
                     update_enemy_kd_tree,
                     update_gold_kd_tree,
@@ -64,40 +72,46 @@ impl Plugin for CollisionPlugin {
                 )
                     .run_if(on_timer(Duration::from_secs_f32(KD_TREE_REFRESH_RATE))),
             )
-                .run_if(in_state(GameState::InGame)),
+                .run_if(playing),
         );
     }
 }
 
 fn handle_enemy_player_collision(
-    player_query: Query<&Transform, With<Player>>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
     tree: Res<EnemyKdTree>,
-    mut ew: EventWriter<PlayerEnemyCollisionEvent>,
+    scaling: Res<DifficultyScaling>,
+    mut ew: EventWriter<DamageEvent>,
 ) {
     if player_query.is_empty() {
         return;
     }
 
-    let player_pos = player_query.single().translation;
+    let (player, player_transform) = player_query.single();
+    let player_pos = player_transform.translation;
     let enemies = tree.0.within_radius(&[player_pos.x, player_pos.y], 40.0);
-    for _ in enemies.iter() {
-        ew.send(PlayerEnemyCollisionEvent);
+    for e in enemies.iter() {
+        let damage = Damage::physical(ENEMY_DAMAGE * scaling.damage);
+        ew.send(DamageEvent::new(player, Some(e.entity), damage));
     }
 }
 
 fn handle_enemy_castle_collision(
-    castle_query: Query<&Transform, With<Castle>>,
+    castle_query: Query<(Entity, &Transform), With<Castle>>,
     tree: Res<EnemyKdTree>,
-    mut ew: EventWriter<CastleEnemyCollisionEvent>,
+    scaling: Res<DifficultyScaling>,
+    mut ew: EventWriter<DamageEvent>,
 ) {
     if castle_query.is_empty() {
         return;
     }
 
-    let castle_pos = castle_query.single().translation;
+    let (castle, castle_transform) = castle_query.single();
+    let castle_pos = castle_transform.translation;
     let enemies = tree.0.within_radius(&[castle_pos.x, castle_pos.y], 150.0);
-    for _ in enemies.iter() {
-        ew.send(CastleEnemyCollisionEvent);
+    for e in enemies.iter() {
+        let damage = Damage::physical(ENEMY_DAMAGE * scaling.damage);
+        ew.send(DamageEvent::new(castle, Some(e.entity), damage));
     }
 }
 
@@ -106,7 +120,7 @@ fn handle_gold_player_collision(
     player_query: Query<&Transform, With<Player>>,
     tree: Res<GoldKdTree>,
     mut ew: EventWriter<PlayerGoldCollisionEvent>,
-    mut gold_query: Query<(&Transform, Entity), With<Gold>>,
+    mut gold_query: Query<(&Gold, Entity)>,
 ) {
     if player_query.is_empty() {
         return;
@@ -116,10 +130,10 @@ fn handle_gold_player_collision(
     let gold = tree.0.within_radius(&[player_pos.x, player_pos.y], 60.0);
 
     for e in gold.iter() {
-        if let Ok((_, entity)) = gold_query.get_mut(e.entity) {
+        if let Ok((gold, entity)) = gold_query.get_mut(e.entity) {
 
             commands.entity(entity).despawn();
-            ew.send(PlayerGoldCollisionEvent);
+            ew.send(PlayerGoldCollisionEvent(gold.0));
         }
 
     }
@@ -155,26 +169,97 @@ fn update_gold_kd_tree(
     tree.0 = KdTree::build_by_ordered_float(items);
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn handle_enemy_bullet_collision(
     mut commands: Commands,
-    bullet_query: Query<(&Transform, Entity), With<Bullet>>,
+    bullet_query: Query<
+        (&Transform, Entity, &Faction, &Damage, Option<&OnHitStatus>),
+        With<Bullet>,
+    >,
     tree: Res<EnemyKdTree>,
-    mut enemy_query: Query<(&Transform, &mut Enemy), With<Enemy>>,
+    enemy_query: Query<&EnemyType, With<Enemy>>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut sfx_events: EventWriter<SfxEvent>,
+    mut shot_events: EventWriter<EnemyShotEvent>,
+    mut status_events: EventWriter<ApplyStatusEvent>,
 ) {
     if bullet_query.is_empty() || enemy_query.is_empty() {
         return;
     }
 
-    for (b_t, entity) in bullet_query.iter() {
+    for (b_t, entity, faction, damage, on_hit) in bullet_query.iter() {
+        if *faction != Faction::Player {
+            continue;
+        }
+
         let pos = b_t.translation;
-        let enemies = tree.0.within_radius(&[pos.x, pos.y], 25.0);
+        let enemies = tree.0.within_radius(&[pos.x, pos.y], BOSS_HIT_RADIUS);
 
         for e in enemies {
-            if let Ok((_, mut enemy)) = enemy_query.get_mut(e.entity) {
-                  enemy.health -= BULLET_DAMAGE;
+            if let Ok(enemy_type) = enemy_query.get(e.entity) {
+                  if e.pos.distance(pos.truncate()) > enemy_type.hit_radius() {
+                        continue;
+                  }
+
+                  damage_events.send(DamageEvent::new(e.entity, None, *damage));
+                  shot_events.send(EnemyShotEvent(e.entity));
+                  if let Some(OnHitStatus(kind)) = on_hit {
+                        status_events.send(ApplyStatusEvent {
+                              target: e.entity,
+                              kind: *kind,
+                        });
+                  }
                   commands.entity(entity).despawn();
+                  sfx_events.send(SfxEvent::at(Sfx::EnemyHit, pos.truncate()));
                   return;
             }
         }
     }
 }
+
+fn handle_bullet_obstacle_collision(
+    mut commands: Commands,
+    bullet_query: Query<(&Transform, Entity), With<Bullet>>,
+    obstacles: Res<Obstacles>,
+) {
+    for (transform, entity) in bullet_query.iter() {
+        if obstacles.contains(transform.translation.truncate()) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn handle_enemy_projectile_collision(
+    mut commands: Commands,
+    bullet_query: Query<(&Transform, Entity, &Faction, &Damage), With<Bullet>>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    castle_query: Query<(Entity, &Transform), With<Castle>>,
+    scaling: Res<DifficultyScaling>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut sfx_events: EventWriter<SfxEvent>,
+) {
+    for (transform, entity, faction, damage) in bullet_query.iter() {
+        if *faction != Faction::Enemy {
+            continue;
+        }
+
+        let pos = transform.translation.truncate();
+        let damage = Damage::new(damage.amount * scaling.damage, damage.kind);
+        if let Some((player, _)) = player_query.iter().find(|(_, t)| {
+            t.translation.truncate().distance(pos) < ENEMY_BULLET_PLAYER_HIT_RADIUS
+        }) {
+            damage_events.send(DamageEvent::new(player, None, damage));
+            sfx_events.send(SfxEvent::at(Sfx::PlayerHit, pos));
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // The castle plays its own hit sound from the damage event.
+        if let Some((castle, _)) = castle_query.iter().find(|(_, t)| {
+            t.translation.truncate().distance(pos) < ENEMY_BULLET_CASTLE_HIT_RADIUS
+        }) {
+            damage_events.send(DamageEvent::new(castle, None, damage));
+            commands.entity(entity).despawn();
+        }
+    }
+}
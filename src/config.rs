@@ -0,0 +1,51 @@
+use std::fs;
+use std::path::PathBuf;
+
+use bevy::log::{error, warn};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::CONFIG_DIR_NAME;
+
+pub fn config_path(file_name: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(CONFIG_DIR_NAME).join(file_name))
+}
+
+pub fn load_config<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    let path = config_path(file_name)?;
+    let contents = fs::read_to_string(&path).ok()?;
+
+    match ron::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warn!("Ignoring invalid config {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+pub fn save_config<T: Serialize>(file_name: &str, value: &T) {
+    let Some(path) = config_path(file_name) else {
+        error!("No config directory available to save {file_name}");
+        return;
+    };
+
+    let contents = match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => contents,
+        Err(err) => {
+            error!("Failed to serialize {file_name}: {err}");
+            return;
+        }
+    };
+
+    if let Some(dir) = path.parent() {
+        if let Err(err) = fs::create_dir_all(dir) {
+            error!("Failed to create config dir {}: {err}", dir.display());
+            return;
+        }
+    }
+
+    if let Err(err) = fs::write(&path, contents) {
+        error!("Failed to write config {}: {err}", path.display());
+    }
+}
@@ -1,6 +1,30 @@
+use bevy::render::color::Color;
+
 //Window
 pub const WW: f32 = 1600.;
 pub const WH: f32 = 900.;
+pub const RESOLUTIONS: [(f32, f32); 4] = [(1280., 720.), (1600., 900.), (1920., 1080.), (2560., 1440.)];
+
+//Camera
+pub const CAMERA_MIN_ZOOM: f32 = 1.5;
+pub const CAMERA_MAX_ZOOM: f32 = 2.5;
+pub const ZOOM_LEVELS: [f32; 7] = [1.0, 1.5, 2.0, 2.5, 3.0, 3.5, 4.0];
+
+//Audio
+pub const VOLUME_STEP: f32 = 0.1;
+pub const MAX_SIMULTANEOUS_SFX: usize = 4;
+pub const AUDIO_SPATIAL_SCALE: f32 = 1.0 / 600.0;
+pub const AUDIO_LISTENER_GAP: f32 = 400.0;
+pub const MENU_MUSIC_PATH: &str = "audio/menu_music.wav";
+pub const GAME_MUSIC_PATH: &str = "audio/game_music.wav";
+pub const SHOOT_SFX_PATH: &str = "audio/shoot.wav";
+pub const ENEMY_HIT_SFX_PATH: &str = "audio/enemy_hit.wav";
+pub const ENEMY_DEATH_SFX_PATH: &str = "audio/enemy_death.wav";
+pub const GOLD_PICKUP_SFX_PATH: &str = "audio/gold_pickup.wav";
+pub const CASTLE_HIT_SFX_PATH: &str = "audio/castle_hit.wav";
+pub const GAME_OVER_SFX_PATH: &str = "audio/game_over.wav";
+pub const ENEMY_SPAWN_SFX_PATH: &str = "audio/enemy_spawn.wav";
+pub const PLAYER_HIT_SFX_PATH: &str = "audio/enemy_hit.wav";
 
 //Assets
 pub const SPRITE_SHEET_PATH: &str = "assets.png";
@@ -25,22 +49,70 @@ pub const COIN_TILE_H: usize = 220;
 pub const COIN_SPRITE_SHEET_W: usize = 6;
 pub const COIN_SPRITE_SHEET_H: usize = 1;
 
+//Config
+pub const CONFIG_DIR_NAME: &str = "hell_game";
+pub const KEYMAP_FILE_NAME: &str = "keymap.ron";
+/// Bindings per action shown and editable on the controls screen.
+pub const BINDING_SLOTS: usize = 2;
+pub const SETTINGS_FILE_NAME: &str = "settings.ron";
+
 //World
-pub const NUM_DECORRATIONS: usize = 3000;
 pub const WORLD_W: f32 = 7000.0;
 pub const WORLD_H: f32 = 7000.0;
+pub const WORLD_BORDER_THICKNESS: f32 = 40.0;
+pub const WORLD_BORDER_COLOR: Color = Color::rgb(0.35, 0.1, 0.1);
+/// Tries at picking a random spawn point inside the world before falling back.
+pub const SPAWN_POSITION_ATTEMPTS: usize = 16;
+pub const CHUNK_SIZE: f32 = 1000.0;
+pub const CHUNK_LOAD_RADIUS: i32 = 3;
+pub const CHUNK_UNLOAD_RADIUS: i32 = 5;
+pub const DECORATIONS_PER_CHUNK: usize = 15;
 
 //Player
 pub const PLAYER_SPEED: f32 = 4.0;
 pub const PLAYER_HEALTH: f32 = 100.0;
+pub const PLAYER_DASH_SPEED_MULTIPLIER: f32 = 4.0;
+pub const PLAYER_DASH_TIME_SECS: f32 = 0.15;
+pub const PLAYER_DASH_COOLDOWN_SECS: f32 = 1.0;
+
+//Gamepad
+pub const GAMEPAD_STICK_DEADZONE: f32 = 0.2;
+pub const GAMEPAD_AIM_DISTANCE: f32 = 200.0;
 
 //Enemy
 pub const MAX_NUM_ENEMIES: usize = 20;
 pub const SPAWN_RATE_PER_SECOND: usize = 2;
 pub const ENEMY_SPAWN_INTERVAL: f32 = 1.0;
 pub const ENEMY_SPEED: f32 = 1.5;
-pub const ENEMY_HEALTH: f32 = 10.0;
+/// Survives a couple of gun hits so shot aggro and fleeing can trigger.
+pub const ENEMY_HEALTH: f32 = 40.0;
+pub const ENEMY_SEPARATION_RADIUS: f32 = 50.0;
+pub const ENEMY_SEPARATION_MAX_NEIGHBOURS: usize = 8;
+pub const GREEN_SEPARATION_WEIGHT: f32 = 1.5;
+pub const RED_SEPARATION_WEIGHT: f32 = 1.0;
+pub const SKIN_SEPARATION_WEIGHT: f32 = 0.6;
+pub const RANGED_SEPARATION_WEIGHT: f32 = 1.2;
+pub const RANGED_ENEMY_STANDOFF: f32 = 350.0;
+pub const RANGED_ENEMY_FIRE_INTERVAL: f32 = 1.5;
+pub const ENEMY_BULLET_SPEED: f32 = 8.0;
+pub const ENEMY_BULLET_DAMAGE: f32 = 5.0;
+pub const ENEMY_HIT_RADIUS: f32 = 25.0;
+pub const ENEMY_BULLET_PLAYER_HIT_RADIUS: f32 = 25.0;
+pub const ENEMY_BULLET_CASTLE_HIT_RADIUS: f32 = 120.0;
+pub const ENEMY_BULLET_SPRITE_INDEX: usize = 17;
 pub const ENEMY_DAMAGE: f32 = 1.0;
+pub const ENEMY_AI_TICK_SECS: f32 = 0.1;
+pub const ENEMY_AGGRO_RADIUS: f32 = 400.0;
+pub const ENEMY_DEAGGRO_RADIUS: f32 = 900.0;
+pub const ENEMY_PLAYER_ATTACK_RANGE: f32 = 40.0;
+pub const ENEMY_CASTLE_ATTACK_RANGE: f32 = 150.0;
+pub const ENEMY_ATTACK_LEASH: f32 = 1.5;
+pub const ENEMY_WANDER_RADIUS: f32 = 300.0;
+pub const ENEMY_WANDER_TIME_SECS: f32 = 2.0;
+pub const ENEMY_WANDER_SPEED_FACTOR: f32 = 0.5;
+pub const ENEMY_FLEE_TIME_SECS: f32 = 2.0;
+pub const ENEMY_FLEE_SPEED_FACTOR: f32 = 1.2;
+pub const ENEMY_FLEE_HEALTH_FRACTION: f32 = 0.3;
 
 // Kd-tree
 pub const KD_TREE_REFRESH_RATE: f32 = 0.1;
@@ -48,7 +120,15 @@ pub const KD_TREE_REFRESH_RATE: f32 = 0.1;
 //Gun
 pub const BULLET_SPAWN_INTERVAL: f32 = 0.1;
 pub const BULLET_SPEED: f32 = 20.0;
-pub const BULLET_DAMAGE: f32 = 15.0;
+pub const GUN_MIN_DAMAGE: f32 = 10.0;
+pub const GUN_MAX_DAMAGE: f32 = 20.0;
+pub const GUN_CRIT_CHANCE: f32 = 0.1;
+pub const GUN_CRIT_MULTIPLIER: f32 = 2.0;
+pub const WEAPON_UPGRADE_BASE_COST: f32 = 20.0;
+pub const WEAPON_DAMAGE_UPGRADE: f32 = 3.0;
+pub const CRIT_CHANCE_UPGRADE: f32 = 0.05;
+pub const CRIT_MULTIPLIER_UPGRADE: f32 = 0.25;
+pub const MAX_CRIT_CHANCE: f32 = 0.75;
 pub const BULLET_TIME_SECS: f32 = 1.;
 pub const NUM_BULLETS_PER_SHOT: usize = 10;
 
@@ -61,7 +141,6 @@ pub const GUN_SPRITE_SHEET_H: usize = 3;
 
 //Menu
 pub const MENU_SPRITE_SHEET_PATH: &str = "bg.png";
-pub const MENU_SPRITE_SCALE_FACTOR: f32 = 3.0;
 pub const MENU_TILE_W: usize = 800;
 pub const MENU_TILE_H: usize = 450;
 pub const MENU_SPRITE_SHEET_W: usize = 12;
@@ -91,3 +170,155 @@ pub const CASTLE_TILE_H: usize = 300;
 pub const CASTLE_SPRITE_SHEET_W: usize = 6;
 pub const CASTLE_SPRITE_SHEET_H: usize = 5;
 pub const CASTLE_HEALTH: f32 = 1000.0;
+pub const CASTLE_ALERT_TIME_SECS: f32 = 3.0;
+
+//Minimap
+pub const MINIMAP_SIZE: f32 = 200.0;
+pub const MINIMAP_DOT_SIZE: f32 = 4.0;
+
+//Threat indicators
+pub const THREAT_CASTLE_RADIUS: f32 = 1500.0;
+pub const THREAT_INDICATOR_SECTORS: usize = 16;
+pub const THREAT_INDICATOR_SIZE: f32 = 24.0;
+pub const THREAT_INDICATOR_MARGIN: f32 = 30.0;
+pub const THREAT_INDICATOR_MAX_COUNT_SCALE: f32 = 2.0;
+pub const THREAT_INDICATOR_FAR_DISTANCE: f32 = 3000.0;
+pub const THREAT_INDICATOR_SPRITE_INDEX: usize = 17;
+
+//Rocks
+pub const ROCK_SPRITE_SHEET_PATH: &str = "rocks.png";
+pub const ROCK_SPRITE_SCALE_FACTOR: f32 = 0.5;
+pub const ROCK_TILE_W: usize = 347;
+pub const ROCK_TILE_H: usize = 310;
+pub const ROCK_SPRITE_SHEET_W: usize = 5;
+pub const ROCK_SPRITE_SHEET_H: usize = 5;
+pub const ROCK_SPRITE_COUNT: usize = 24;
+pub const ROCKS_PER_CHUNK: usize = 3;
+pub const ROCK_CLEARANCE_AROUND_CASTLE: f32 = 600.0;
+/// Upper bound of any rock collider's extent, used to widen kd-tree queries.
+pub const ROCK_MAX_EXTENT: f32 = 150.0;
+pub const PLAYER_COLLISION_RADIUS: f32 = 20.0;
+pub const ENEMY_COLLISION_RADIUS: f32 = 20.0;
+
+//Navigation
+/// Divides `CHUNK_SIZE` so chunk borders fall on cell borders.
+pub const NAV_CELL_SIZE: f32 = 100.0;
+pub const CASTLE_FOOTPRINT_RADIUS: f32 = 150.0;
+
+//Boss
+pub const BOSS_SPAWN_INTERVAL_SECS: f32 = 90.0;
+pub const BOSS_SPAWN_DISTANCE: f32 = 1200.0;
+pub const BOSS_HEALTH: f32 = 1500.0;
+pub const BOSS_SCALE_MULTIPLIER: f32 = 3.0;
+pub const BOSS_HIT_RADIUS: f32 = 70.0;
+pub const BOSS_PLAYER_ATTACK_RANGE: f32 = 80.0;
+pub const BOSS_SEPARATION_WEIGHT: f32 = 0.2;
+pub const BOSS_ABILITY_INTERVAL_SECS: f32 = 4.0;
+pub const BOSS_SUMMON_COUNT: usize = 4;
+pub const BOSS_SUMMON_RADIUS: f32 = 200.0;
+pub const BOSS_CHARGE_TIME_SECS: f32 = 0.6;
+pub const BOSS_CHARGE_SPEED: f32 = 12.0;
+pub const BOSS_BARRAGE_BULLETS: usize = 16;
+pub const BOSS_GOLD_REWARD: usize = 25;
+pub const BOSS_REWARD_SCATTER: f32 = 150.0;
+pub const BOSS_CRYSTAL_VALUE: f32 = 50.0;
+pub const BOSS_BAR_HEIGHT: f32 = 24.0;
+
+//Crystals
+pub const CRYSTAL_SPRITE_SHEET_PATH: &str = "red_crystal.png";
+pub const CRYSTAL_SPRITE_SCALE_FACTOR: f32 = 0.3;
+pub const CRYSTAL_TILE_W: usize = 200;
+pub const CRYSTAL_TILE_H: usize = 290;
+pub const CRYSTAL_SPRITE_SHEET_W: usize = 6;
+pub const CRYSTAL_SPRITE_SHEET_H: usize = 2;
+
+//Elites
+pub const ELITE_CHANCE: f32 = 0.1;
+pub const ELITE_MAX_AFFIXES: usize = 2;
+pub const ARMORED_DAMAGE_REDUCTION: f32 = 0.5;
+pub const HASTED_SPEED_MULTIPLIER: f32 = 1.6;
+pub const REGENERATION_PER_SECOND: f32 = 2.0;
+pub const VAMPIRIC_HEAL: f32 = 1.0;
+pub const SPLIT_COUNT: usize = 2;
+pub const SPLIT_SCALE: f32 = 0.6;
+pub const SPLIT_SCATTER: f32 = 30.0;
+
+//Portals
+pub const PORTAL_COUNT: usize = 6;
+pub const PORTAL_RESPAWN_SECS: f32 = 10.0;
+pub const PORTAL_MIN_DISTANCE: f32 = 800.0;
+pub const PORTAL_MAX_DISTANCE: f32 = 2500.0;
+pub const PORTAL_FORGET_DISTANCE: f32 = 5000.0;
+pub const PORTAL_WARNING_SECS: f32 = 1.5;
+pub const PORTAL_HEALTH: f32 = 300.0;
+pub const PORTAL_HIT_RADIUS: f32 = 40.0;
+pub const PORTAL_SPAWN_SCATTER: f32 = 60.0;
+pub const PORTAL_SPRITE_INDEX: usize = 59;
+pub const PORTAL_SPRITE_SCALE_FACTOR: f32 = 4.0;
+pub const PORTAL_IDLE_COLOR: Color = Color::rgb(0.7, 0.4, 1.0);
+pub const PORTAL_WARNING_COLOR: Color = Color::rgb(1.0, 0.2, 0.2);
+
+//Difficulty
+pub const DIFFICULTY_RAMP_SECS: f32 = 300.0;
+pub const DIFFICULTY_MAX_RAMP: f32 = 2.0;
+pub const DIFFICULTY_SPEED_RAMP_SHARE: f32 = 0.25;
+
+//Director
+pub const DIRECTOR_SAMPLE_SECS: f32 = 1.0;
+pub const DIRECTOR_WINDOW_SAMPLES: usize = 30;
+pub const DIRECTOR_TARGET_INTENSITY: f32 = 0.4;
+pub const DIRECTOR_ADJUST_RATE: f32 = 0.05;
+pub const DIRECTOR_MAX_BIAS: f32 = 0.5;
+pub const DIRECTOR_ELITE_BIAS_SCALE: f32 = 2.0;
+/// Combined player and castle health fraction lost over the window that counts as full stress.
+pub const DIRECTOR_DAMAGE_SCALE: f32 = 0.3;
+pub const DIRECTOR_KILL_RATE_SCALE: f32 = 2.0;
+pub const DIRECTOR_GOLD_RATE_SCALE: f32 = 2.0;
+
+//Status effects
+pub const BURN_DAMAGE_PER_SECOND: f32 = 10.0;
+pub const BURN_TIME_SECS: f32 = 3.0;
+pub const POISON_DAMAGE_PER_SECOND: f32 = 4.0;
+pub const POISON_TIME_SECS: f32 = 8.0;
+pub const SLOW_SPEED_MULTIPLIER: f32 = 0.5;
+pub const SLOW_TIME_SECS: f32 = 3.0;
+pub const FREEZE_TIME_SECS: f32 = 1.0;
+
+//Damage
+pub const MAX_RESISTANCE: f32 = 0.9;
+/// Armour value that halves physical damage.
+pub const ARMOR_SCALE: f32 = 50.0;
+pub const PLAYER_ARMOR: f32 = 10.0;
+pub const PLAYER_SHIELD: f32 = 25.0;
+pub const CASTLE_ARMOR: f32 = 25.0;
+pub const CASTLE_SHIELD: f32 = 200.0;
+pub const SHIELD_REGEN_PER_SECOND: f32 = 2.0;
+
+//Damage numbers
+pub const DAMAGE_NUMBER_MIN_AMOUNT: f32 = 1.0;
+pub const DAMAGE_NUMBER_TIME_SECS: f32 = 0.8;
+pub const DAMAGE_NUMBER_RISE_SPEED: f32 = 60.0;
+pub const DAMAGE_NUMBER_FONT_SIZE: f32 = 32.0;
+pub const DAMAGE_NUMBER_CRIT_FONT_SIZE: f32 = 48.0;
+pub const DAMAGE_NUMBER_Z: f32 = 50.0;
+
+//Explosions
+pub const ROCKET_COOLDOWN_SECS: f32 = 1.5;
+pub const ROCKET_SPEED: f32 = 12.0;
+pub const ROCKET_FUSE_SECS: f32 = 1.2;
+pub const ROCKET_DAMAGE_MULTIPLIER: f32 = 3.0;
+pub const ROCKET_SPRITE_SCALE_FACTOR: f32 = 5.0;
+pub const EXPLOSION_RADIUS: f32 = 180.0;
+/// Share of the damage still dealt at the very edge of the blast.
+pub const EXPLOSION_MIN_FALLOFF: f32 = 0.3;
+pub const EXPLOSION_KNOCKBACK_SPEED: f32 = 900.0;
+pub const EXPLOSION_KNOCKBACK_TIME_SECS: f32 = 0.25;
+pub const BOSS_KNOCKBACK_FACTOR: f32 = 0.2;
+/// Share of a player explosion's damage the castle takes when caught in the blast.
+pub const EXPLOSION_CASTLE_FRIENDLY_FIRE: f32 = 0.25;
+pub const EXPLOSION_SPRITE_SHEET_PATH: &str = "explosion.png";
+pub const EXPLOSION_TILE_W: usize = 32;
+pub const EXPLOSION_TILE_H: usize = 32;
+pub const EXPLOSION_SPRITE_SHEET_W: usize = 8;
+pub const EXPLOSION_SPRITE_SHEET_H: usize = 1;
+pub const EXPLOSION_FRAME_SECS: f32 = 0.05;
@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 
+use crate::input::{ActionState, InputAction};
 use crate::{
     state::GameState, world::GameEntity, GlobalTextureAtlas, CURSOR_SPRITE_SCALE_FACTOR
 };
@@ -21,7 +22,12 @@ fn setup_cursor(
     mut windows: Query<&mut Window>,
     mut commands: Commands,
     handle: Res<GlobalTextureAtlas>,
+    cursor_query: Query<(), With<GameCursor>>,
 ) {
+    if !cursor_query.is_empty() {
+        return;
+    }
+
         let mut window: Mut<Window> = windows.single_mut();
         window.cursor.visible = false;
     
@@ -48,6 +54,7 @@ fn setup_cursor(
 fn move_cursor(
     window: Query<&mut Window>,
     mut cursor_query: Query<&mut Style, With<GameCursor>>,
+    ui_scale: Res<UiScale>,
 ) {
     if cursor_query.is_empty() {
         return;
@@ -56,8 +63,8 @@ fn move_cursor(
     let windows: &Window = window.single();
     if let Some(position) = windows.cursor_position() {
             let mut style = cursor_query.single_mut();
-            style.left = Val::Px(position.x);
-            style.top = Val::Px(position.y);
+            style.left = Val::Px(position.x / ui_scale.0);
+            style.top = Val::Px(position.y / ui_scale.0);
     }
 }
 
@@ -104,7 +111,7 @@ fn set_gun_cursor(mut cursor_query: Query<&mut TextureAtlas, With<GameCursor>>)
 // }
 
 fn on_click_cursor(
-      mouse_button_input: Res<ButtonInput<MouseButton>>,
+      actions: Res<ActionState>,
       mut cursor_query: Query<(&mut Transform, &mut TextureAtlas), With<GameCursor>>,
       state: Res<State<GameState>>,
 ) {
@@ -114,16 +121,16 @@ fn on_click_cursor(
 
       let (mut transform, mut atlas) = cursor_query.single_mut();
 
-      if mouse_button_input.pressed(MouseButton::Left) {
+      if actions.pressed(InputAction::Fire) {
             transform.scale = Vec3::splat(CURSOR_SPRITE_SCALE_FACTOR - 0.1);
-      } else if mouse_button_input.pressed(MouseButton::Right) {
+      } else if actions.pressed(InputAction::PanCamera) {
             atlas.index = 2;
       } else {
             transform.scale = Vec3::splat(CURSOR_SPRITE_SCALE_FACTOR);
 
             if *state.get() == GameState::InGame {
                   atlas.index = 0;
-            } else if *state.get() == GameState::MainMenu {
+            } else {
                   atlas.index = 1;
             }
       }
@@ -0,0 +1,322 @@
+use bevy::prelude::*;
+
+use crate::enemy::EnemyType;
+use crate::state::playing;
+use crate::status::StatusKind;
+use crate::*;
+
+pub struct DamagePlugin;
+
+/// System set to order readers of `DeathEvent` after damage is applied.
+#[derive(Debug, Clone, Copy, SystemSet, PartialEq, Eq, Hash)]
+pub struct DamageSystemSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageKind {
+    Physical,
+    Fire,
+    Frost,
+    Poison,
+    Holy,
+}
+
+/// A typed hit, carried by projectiles and produced by contact attacks and status ticks.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Damage {
+    pub amount: f32,
+    pub kind: DamageKind,
+    pub crit: bool,
+}
+
+/// Fraction of each damage kind ignored; negative values are weaknesses.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Resistances {
+    pub physical: f32,
+    pub fire: f32,
+    pub frost: f32,
+    pub poison: f32,
+    pub holy: f32,
+}
+
+/// Reduces physical damage, with diminishing returns as it grows.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Armor(pub f32);
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+/// Raw damage dealt to anything with `Health`; reductions are applied when it lands.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub amount: f32,
+    pub kind: DamageKind,
+    pub crit: bool,
+}
+
+/// What a `DamageEvent` actually took off after reductions, for feedback.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DamageDealtEvent {
+    pub target: Entity,
+    pub amount: f32,
+    pub crit: bool,
+}
+
+/// Sent once when an entity's health first drops to zero.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    pub source: Option<Entity>,
+}
+
+/// Absorbs damage before health and slowly recharges.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Shield {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_event::<DamageDealtEvent>()
+            .add_event::<DeathEvent>()
+            .add_systems(
+                Update,
+                (apply_damage_events, regenerate_shields)
+                    .run_if(playing)
+                    .in_set(DamageSystemSet),
+            );
+    }
+}
+
+impl Damage {
+    pub fn new(amount: f32, kind: DamageKind) -> Self {
+        Self {
+            amount,
+            kind,
+            crit: false,
+        }
+    }
+
+    pub fn physical(amount: f32) -> Self {
+        Self::new(amount, DamageKind::Physical)
+    }
+}
+
+impl DamageEvent {
+    pub fn new(target: Entity, source: Option<Entity>, damage: Damage) -> Self {
+        Self {
+            target,
+            source,
+            amount: damage.amount,
+            kind: damage.kind,
+            crit: damage.crit,
+        }
+    }
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        (self.current / self.max).clamp(0.0, 1.0)
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    /// Dead entities stay dead until they are despawned.
+    pub fn heal(&mut self, amount: f32) {
+        if !self.is_dead() {
+            self.current = (self.current + amount).min(self.max);
+        }
+    }
+}
+
+impl From<StatusKind> for DamageKind {
+    fn from(kind: StatusKind) -> Self {
+        match kind {
+            StatusKind::Burn => DamageKind::Fire,
+            StatusKind::Slow | StatusKind::Freeze => DamageKind::Frost,
+            StatusKind::Poison => DamageKind::Poison,
+        }
+    }
+}
+
+impl Resistances {
+    pub fn get(&self, kind: DamageKind) -> f32 {
+        match kind {
+            DamageKind::Physical => self.physical,
+            DamageKind::Fire => self.fire,
+            DamageKind::Frost => self.frost,
+            DamageKind::Poison => self.poison,
+            DamageKind::Holy => self.holy,
+        }
+    }
+
+    /// Raises every resistance by `amount`, used by the armored elite affix.
+    pub fn hardened(self, amount: f32) -> Self {
+        Self {
+            physical: self.physical + amount,
+            fire: self.fire + amount,
+            frost: self.frost + amount,
+            poison: self.poison + amount,
+            holy: self.holy + amount,
+        }
+    }
+}
+
+impl Shield {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+}
+
+impl EnemyType {
+    pub fn resistances(&self) -> Resistances {
+        let (physical, fire, frost, poison, holy) = match self {
+            EnemyType::Green => (0.0, -0.25, 0.0, 0.25, 0.0),
+            EnemyType::Red => (0.0, 0.5, -0.25, 0.0, 0.0),
+            EnemyType::Skin => (0.25, 0.0, 0.0, -0.25, -0.25),
+            EnemyType::Ranged => (-0.1, 0.0, 0.25, 0.0, 0.0),
+            EnemyType::Boss => (0.25, 0.25, 0.25, 0.25, -0.25),
+        };
+
+        Resistances {
+            physical,
+            fire,
+            frost,
+            poison,
+            holy,
+        }
+    }
+}
+
+/// The single place incoming damage is reduced: resistances, then armour, then shields.
+/// Returns what is left to take off health.
+pub fn resolve_damage(
+    damage: Damage,
+    resistances: Option<&Resistances>,
+    armor: Option<&Armor>,
+    shield: Option<&mut Shield>,
+) -> f32 {
+    let resistance = resistances
+        .map_or(0.0, |r| r.get(damage.kind))
+        .min(MAX_RESISTANCE);
+    let mut amount = damage.amount * (1.0 - resistance);
+
+    if let (Some(Armor(armor)), DamageKind::Physical) = (armor, damage.kind) {
+        amount *= ARMOR_SCALE / (ARMOR_SCALE + armor.max(0.0));
+    }
+
+    if let Some(shield) = shield {
+        let absorbed = amount.min(shield.current);
+        shield.current -= absorbed;
+        amount -= absorbed;
+    }
+
+    amount.max(0.0)
+}
+
+#[allow(clippy::type_complexity)]
+fn apply_damage_events(
+    mut events: EventReader<DamageEvent>,
+    mut dealt_events: EventWriter<DamageDealtEvent>,
+    mut death_events: EventWriter<DeathEvent>,
+    mut target_query: Query<(
+        &mut Health,
+        Option<&Resistances>,
+        Option<&Armor>,
+        Option<&mut Shield>,
+    )>,
+) {
+    for event in events.read() {
+        let Ok((mut health, resistances, armor, mut shield)) = target_query.get_mut(event.target)
+        else {
+            continue;
+        };
+        if health.is_dead() {
+            continue;
+        }
+
+        let damage = Damage::new(event.amount, event.kind);
+        let amount = resolve_damage(damage, resistances, armor, shield.as_deref_mut());
+        health.current -= amount;
+        dealt_events.send(DamageDealtEvent {
+            target: event.target,
+            amount,
+            crit: event.crit,
+        });
+        if health.is_dead() {
+            death_events.send(DeathEvent {
+                entity: event.target,
+                source: event.source,
+            });
+        }
+    }
+}
+
+fn regenerate_shields(time: Res<Time>, mut shield_query: Query<&mut Shield>) {
+    for mut shield in shield_query.iter_mut() {
+        if shield.current < shield.max {
+            shield.current =
+                (shield.current + SHIELD_REGEN_PER_SECOND * time.delta_seconds()).min(shield.max);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resistances(amount: f32) -> Resistances {
+        Resistances::default().hardened(amount)
+    }
+
+    #[test]
+    fn resistance_is_capped() {
+        let amount = resolve_damage(Damage::physical(100.0), Some(&resistances(2.0)), None, None);
+        assert!((amount - 100.0 * (1.0 - MAX_RESISTANCE)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn negative_resistance_is_a_weakness() {
+        let damage = Damage::new(100.0, DamageKind::Fire);
+        let amount = resolve_damage(damage, Some(&resistances(-0.5)), None, None);
+        assert!((amount - 150.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn armor_only_reduces_physical() {
+        let armor = Armor(ARMOR_SCALE);
+        let physical = resolve_damage(Damage::physical(100.0), None, Some(&armor), None);
+        let fire = resolve_damage(
+            Damage::new(100.0, DamageKind::Fire),
+            None,
+            Some(&armor),
+            None,
+        );
+        assert!((physical - 50.0).abs() < 1e-4);
+        assert!((fire - 100.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn shield_absorbs_before_health() {
+        let mut shield = Shield::new(30.0);
+        let amount = resolve_damage(Damage::physical(20.0), None, None, Some(&mut shield));
+        assert_eq!(amount, 0.0);
+        assert!((shield.current - 10.0).abs() < 1e-4);
+
+        let amount = resolve_damage(Damage::physical(20.0), None, None, Some(&mut shield));
+        assert!((amount - 10.0).abs() < 1e-4);
+        assert_eq!(shield.current, 0.0);
+    }
+}
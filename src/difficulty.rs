@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::damage::Health;
+use crate::enemy::Enemy;
+use crate::settings::Settings;
+use crate::state::{playing, GameState};
+use crate::*;
+
+pub struct DifficultyPlugin;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Nightmare,
+}
+
+/// Multipliers applied to enemy stats, combining the chosen preset with the run's elapsed time.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct DifficultyScaling {
+    pub health: f32,
+    pub damage: f32,
+    pub speed: f32,
+    pub count: f32,
+    pub ramp: f32,
+}
+
+/// Seconds spent in game since the run started.
+#[derive(Resource, Default)]
+pub struct RunTime(pub f32);
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunTime>()
+            .insert_resource(DifficultyScaling::default())
+            .add_systems(OnEnter(GameState::GameInit), reset_run_time)
+            .add_systems(
+                Update,
+                (update_difficulty_scaling, scale_new_enemies)
+                    .chain()
+                    .run_if(playing),
+            );
+    }
+}
+
+impl Difficulty {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Nightmare => "Nightmare",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Nightmare,
+            Difficulty::Nightmare => Difficulty::Easy,
+        }
+    }
+
+    /// Scaling at the very start of a run.
+    fn base(&self) -> DifficultyScaling {
+        let (health, damage, speed, count) = match self {
+            Difficulty::Easy => (0.7, 0.5, 0.85, 0.75),
+            Difficulty::Normal => (1.0, 1.0, 1.0, 1.0),
+            Difficulty::Hard => (1.5, 1.5, 1.15, 1.5),
+            Difficulty::Nightmare => (2.5, 2.0, 1.3, 2.0),
+        };
+
+        DifficultyScaling {
+            health,
+            damage,
+            speed,
+            count,
+            ramp: 1.0,
+        }
+    }
+
+    /// How quickly the curve climbs relative to `DIFFICULTY_RAMP_SECS`.
+    fn ramp_rate(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+            Difficulty::Nightmare => 2.0,
+        }
+    }
+
+    pub fn scaling(&self, elapsed_secs: f32) -> DifficultyScaling {
+        let ramp =
+            1.0 + (elapsed_secs / DIFFICULTY_RAMP_SECS * self.ramp_rate()).min(DIFFICULTY_MAX_RAMP);
+        let base = self.base();
+
+        DifficultyScaling {
+            health: base.health * ramp,
+            damage: base.damage * ramp,
+            speed: base.speed * (1.0 + (ramp - 1.0) * DIFFICULTY_SPEED_RAMP_SHARE),
+            count: base.count * ramp,
+            ramp,
+        }
+    }
+}
+
+impl Default for DifficultyScaling {
+    fn default() -> Self {
+        Difficulty::default().base()
+    }
+}
+
+impl DifficultyScaling {
+    pub fn max_enemies(&self) -> usize {
+        (MAX_NUM_ENEMIES as f32 * self.count).round() as usize
+    }
+
+    pub fn spawn_rate(&self) -> usize {
+        (SPAWN_RATE_PER_SECOND as f32 * self.count).round().max(1.0) as usize
+    }
+}
+
+fn reset_run_time(mut run_time: ResMut<RunTime>) {
+    run_time.0 = 0.0;
+}
+
+fn update_difficulty_scaling(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut run_time: ResMut<RunTime>,
+    mut scaling: ResMut<DifficultyScaling>,
+) {
+    run_time.0 += time.delta_seconds();
+    scaling.set_if_neq(settings.difficulty.scaling(run_time.0));
+}
+
+fn scale_new_enemies(
+    scaling: Res<DifficultyScaling>,
+    mut enemy_query: Query<&mut Health, Added<Enemy>>,
+) {
+    for mut health in enemy_query.iter_mut() {
+        health.current *= scaling.health;
+        health.max *= scaling.health;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaling_starts_at_the_preset_base() {
+        assert_eq!(
+            Difficulty::Normal.scaling(0.0),
+            DifficultyScaling::default()
+        );
+        assert_eq!(Difficulty::Hard.scaling(0.0).health, 1.5);
+    }
+
+    #[test]
+    fn ramp_is_capped() {
+        let capped = Difficulty::Normal.scaling(DIFFICULTY_RAMP_SECS * DIFFICULTY_MAX_RAMP);
+        let later = Difficulty::Normal.scaling(DIFFICULTY_RAMP_SECS * DIFFICULTY_MAX_RAMP * 10.0);
+        assert_eq!(capped, later);
+        assert_eq!(capped.ramp, 1.0 + DIFFICULTY_MAX_RAMP);
+    }
+
+    #[test]
+    fn harder_presets_ramp_faster() {
+        let secs = DIFFICULTY_RAMP_SECS / 2.0;
+        assert!(Difficulty::Easy.scaling(secs).ramp < Difficulty::Normal.scaling(secs).ramp);
+        assert!(Difficulty::Normal.scaling(secs).ramp < Difficulty::Nightmare.scaling(secs).ramp);
+    }
+
+    #[test]
+    fn speed_ramps_slower_than_health() {
+        let scaling = Difficulty::Normal.scaling(DIFFICULTY_RAMP_SECS);
+        assert!(scaling.speed > 1.0 && scaling.speed < scaling.health);
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use bevy::utils::Duration;
+
+use crate::castle::Castle;
+use crate::damage::{DamageSystemSet, DeathEvent, Health};
+use crate::enemy::Enemy;
+use crate::gold::PlayerGoldCollisionEvent;
+use crate::player::Player;
+use crate::settings::Settings;
+use crate::state::{playing, GameState};
+use crate::*;
+
+pub struct DirectorPlugin;
+
+#[derive(Debug, Clone, Copy)]
+struct DirectorSample {
+    player_health: f32,
+    castle_health: f32,
+    kills: usize,
+    gold: f32,
+}
+
+/// Watches how the run is going and nudges spawn rate and elite chance to keep it tense.
+#[derive(Resource, Default)]
+pub struct Director {
+    /// How hard the run currently feels, from 0 (coasting) to 1 (overwhelmed).
+    pub intensity: f32,
+    /// Positive when the director is pushing harder than the base difficulty.
+    pub bias: f32,
+    samples: VecDeque<DirectorSample>,
+    kills: usize,
+    gold: f32,
+}
+
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Director>()
+            .add_systems(OnEnter(GameState::GameInit), reset_director)
+            .add_systems(
+                Update,
+                (
+                    record_director_activity.after(DamageSystemSet),
+                    update_director.run_if(on_timer(Duration::from_secs_f32(DIRECTOR_SAMPLE_SECS))),
+                )
+                    .chain()
+                    .run_if(playing)
+                    .run_if(director_enabled),
+            );
+    }
+}
+
+impl Director {
+    pub fn spawn_rate_multiplier(&self) -> f32 {
+        1.0 + self.bias
+    }
+
+    pub fn elite_chance(&self) -> f32 {
+        (ELITE_CHANCE * (1.0 + self.bias * DIRECTOR_ELITE_BIAS_SCALE)).clamp(0.0, 1.0)
+    }
+
+    fn measure_intensity(&self) -> f32 {
+        let (Some(oldest), Some(latest)) = (self.samples.front(), self.samples.back()) else {
+            return DIRECTOR_TARGET_INTENSITY;
+        };
+
+        let window_secs = self.samples.len() as f32 * DIRECTOR_SAMPLE_SECS;
+        let kill_rate = self.samples.iter().map(|s| s.kills).sum::<usize>() as f32 / window_secs;
+        let gold_rate = self.samples.iter().map(|s| s.gold).sum::<f32>() / window_secs;
+
+        let health_stress = 1.0 - latest.player_health.min(latest.castle_health);
+        let damage_taken = (oldest.player_health - latest.player_health).max(0.0)
+            + (oldest.castle_health - latest.castle_health).max(0.0);
+        let damage_stress = (damage_taken / DIRECTOR_DAMAGE_SCALE).min(1.0);
+        let dominance =
+            ((kill_rate / DIRECTOR_KILL_RATE_SCALE + gold_rate / DIRECTOR_GOLD_RATE_SCALE) / 2.0)
+                .min(1.0);
+
+        ((health_stress + damage_stress + 1.0 - dominance) / 3.0).clamp(0.0, 1.0)
+    }
+
+    /// Adds a sample to the sliding window and nudges the bias toward the target intensity.
+    fn push_sample(&mut self, sample: DirectorSample) {
+        self.samples.push_back(sample);
+        if self.samples.len() > DIRECTOR_WINDOW_SAMPLES {
+            self.samples.pop_front();
+        }
+
+        self.intensity = self.measure_intensity();
+        let nudge = (DIRECTOR_TARGET_INTENSITY - self.intensity) * DIRECTOR_ADJUST_RATE;
+        self.bias = (self.bias + nudge).clamp(-DIRECTOR_MAX_BIAS, DIRECTOR_MAX_BIAS);
+    }
+}
+
+fn director_enabled(settings: Res<Settings>) -> bool {
+    settings.adaptive_director
+}
+
+fn reset_director(mut director: ResMut<Director>) {
+    *director = Director::default();
+}
+
+/// Counts kills and gold every frame so nothing is missed between samples.
+fn record_director_activity(
+    mut director: ResMut<Director>,
+    enemy_query: Query<(), With<Enemy>>,
+    mut death_events: EventReader<DeathEvent>,
+    mut gold_events: EventReader<PlayerGoldCollisionEvent>,
+) {
+    director.kills += death_events
+        .read()
+        .filter(|event| enemy_query.contains(event.entity))
+        .count();
+    director.gold += gold_events.read().map(|event| event.0).sum::<f32>();
+}
+
+fn update_director(
+    mut director: ResMut<Director>,
+    player_query: Query<&Health, With<Player>>,
+    castle_query: Query<&Health, (With<Castle>, Without<Player>)>,
+) {
+    let (Ok(player_health), Ok(castle_health)) =
+        (player_query.get_single(), castle_query.get_single())
+    else {
+        return;
+    };
+
+    let sample = DirectorSample {
+        player_health: player_health.fraction(),
+        castle_health: castle_health.fraction(),
+        kills: std::mem::take(&mut director.kills),
+        gold: std::mem::take(&mut director.gold),
+    };
+    director.push_sample(sample);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALM: DirectorSample = DirectorSample {
+        player_health: 1.0,
+        castle_health: 1.0,
+        kills: 10,
+        gold: 10.0,
+    };
+
+    fn stressed(health: f32) -> DirectorSample {
+        DirectorSample {
+            player_health: health,
+            castle_health: 1.0,
+            kills: 0,
+            gold: 0.0,
+        }
+    }
+
+    #[test]
+    fn empty_window_sits_at_the_target() {
+        assert_eq!(
+            Director::default().measure_intensity(),
+            DIRECTOR_TARGET_INTENSITY
+        );
+    }
+
+    #[test]
+    fn dominating_pushes_harder() {
+        let mut director = Director::default();
+        director.push_sample(CALM);
+        assert!(director.intensity < DIRECTOR_TARGET_INTENSITY);
+        assert!(director.bias > 0.0);
+    }
+
+    #[test]
+    fn losing_health_eases_off() {
+        let mut director = Director::default();
+        director.push_sample(stressed(1.0));
+        director.push_sample(stressed(0.2));
+        assert!(director.intensity > DIRECTOR_TARGET_INTENSITY);
+        assert!(director.bias < 0.0);
+    }
+
+    #[test]
+    fn bias_is_capped() {
+        let mut director = Director::default();
+        for _ in 0..1000 {
+            director.push_sample(CALM);
+        }
+        assert_eq!(director.bias, DIRECTOR_MAX_BIAS);
+    }
+
+    #[test]
+    fn old_samples_leave_the_window() {
+        let mut director = Director::default();
+        director.push_sample(stressed(1.0));
+        director.push_sample(stressed(0.2));
+        for _ in 0..DIRECTOR_WINDOW_SAMPLES {
+            director.push_sample(CALM);
+        }
+        assert_eq!(director.samples.len(), DIRECTOR_WINDOW_SAMPLES);
+
+        let mut calm = Director::default();
+        for _ in 0..DIRECTOR_WINDOW_SAMPLES {
+            calm.push_sample(CALM);
+        }
+        assert_eq!(director.intensity, calm.intensity);
+    }
+}
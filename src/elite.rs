@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::castle::Castle;
+use crate::damage::{DamageEvent, Health};
+use crate::enemy::Enemy;
+use crate::state::playing;
+use crate::*;
+
+pub struct ElitePlugin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EliteAffix {
+    Armored,
+    Hasted,
+    Regenerating,
+    Vampiric,
+    Splitting,
+}
+
+#[derive(Component, Debug, Clone, Default)]
+pub struct EliteAffixes(pub Vec<EliteAffix>);
+
+impl Plugin for ElitePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (regenerate_elites, handle_vampiric_hits).run_if(playing),
+        );
+    }
+}
+
+impl EliteAffix {
+    pub const ALL: [EliteAffix; 5] = [
+        EliteAffix::Armored,
+        EliteAffix::Hasted,
+        EliteAffix::Regenerating,
+        EliteAffix::Vampiric,
+        EliteAffix::Splitting,
+    ];
+
+    pub fn tint(&self) -> Color {
+        match self {
+            EliteAffix::Armored => Color::rgb(0.6, 0.7, 1.0),
+            EliteAffix::Hasted => Color::rgb(1.0, 1.0, 0.4),
+            EliteAffix::Regenerating => Color::rgb(0.5, 1.0, 0.5),
+            EliteAffix::Vampiric => Color::rgb(1.0, 0.4, 0.5),
+            EliteAffix::Splitting => Color::rgb(1.0, 0.6, 1.0),
+        }
+    }
+}
+
+impl EliteAffixes {
+    /// Rolls `chance` for an elite with up to `ELITE_MAX_AFFIXES` distinct affixes.
+    pub fn roll(chance: f32) -> Option<Self> {
+        let mut rng = rand::thread_rng();
+        if !rng.gen_bool(chance.clamp(0.0, 1.0) as f64) {
+            return None;
+        }
+
+        let count = rng.gen_range(1..=ELITE_MAX_AFFIXES);
+        let affixes = EliteAffix::ALL
+            .choose_multiple(&mut rng, count)
+            .copied()
+            .collect();
+
+        Some(Self(affixes))
+    }
+
+    pub fn has(&self, affix: EliteAffix) -> bool {
+        self.0.contains(&affix)
+    }
+
+    /// Blend of every affix tint, applied to the enemy sprite.
+    pub fn tint(&self) -> Color {
+        if self.0.is_empty() {
+            return Color::WHITE;
+        }
+
+        let sum = self
+            .0
+            .iter()
+            .map(|affix| Vec4::from(affix.tint().as_rgba_f32()))
+            .sum::<Vec4>();
+
+        Color::rgba_from_array(sum / self.0.len() as f32)
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.has(EliteAffix::Hasted) {
+            HASTED_SPEED_MULTIPLIER
+        } else {
+            1.0
+        }
+    }
+}
+
+fn regenerate_elites(
+    time: Res<Time>,
+    mut enemy_query: Query<(&mut Health, &EliteAffixes), With<Enemy>>,
+) {
+    for (mut health, affixes) in enemy_query.iter_mut() {
+        if affixes.has(EliteAffix::Regenerating) {
+            health.heal(REGENERATION_PER_SECOND * time.delta_seconds());
+        }
+    }
+}
+
+/// Vampiric elites heal whenever they damage the castle.
+fn handle_vampiric_hits(
+    mut events: EventReader<DamageEvent>,
+    castle_query: Query<(), With<Castle>>,
+    mut enemy_query: Query<(&mut Health, &EliteAffixes), With<Enemy>>,
+) {
+    for event in events.read() {
+        if !castle_query.contains(event.target) {
+            continue;
+        }
+        let Some(Ok((mut health, affixes))) = event.source.map(|source| enemy_query.get_mut(source))
+        else {
+            continue;
+        };
+
+        if affixes.has(EliteAffix::Vampiric) {
+            health.heal(VAMPIRIC_HEAL);
+        }
+    }
+}
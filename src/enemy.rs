@@ -1,11 +1,23 @@
-use std::f32::consts::PI;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use crate::audio::{Sfx, SfxEvent};
+use crate::ai::{AiState, EnemyAi};
+use crate::boss::Boss;
+use crate::damage::{Damage, DeathEvent, Health};
+use crate::difficulty::DifficultyScaling;
+use crate::director::Director;
+use crate::elite::{EliteAffix, EliteAffixes};
+use crate::gun::{Bullet, BulletDirection, Faction, SpawnInstant};
+use crate::collision::{Collidable, EnemyKdTree};
+use crate::navigation::FlowField;
+use crate::obstacle::Obstacles;
 use crate::player::Player;
-use crate::state::GameState;
+use crate::portal::Portal;
+use crate::state::playing;
+use crate::status::{StatusEffects, StatusKind};
 use crate::*;
 use animation::AnimationTimer;
-use bevy::math::vec3;
+use bevy::math::{vec2, vec3};
 use bevy::{prelude::*, time::common_conditions::on_timer};
 use castle::Castle;
 use gold::Gold;
@@ -13,34 +25,51 @@ use rand::Rng;
 use world::GameEntity;
 
 #[derive(Component)]
-pub struct Enemy {
-    pub health: f32,
-}
-
-impl Default for Enemy {
-    fn default() -> Self {
-        Self {
-            health: ENEMY_HEALTH,
-        }
-    }
-}
+pub struct Enemy;
 
-#[derive(Component, PartialEq, Eq)]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EnemyType {
     Green,
     Red,
     Skin,
+    Ranged,
+    Boss,
 }
 
+#[derive(Component)]
+pub struct RangedAttack(pub Timer);
+
 impl EnemyType {
     fn get_rand_enemy() -> Self {
         let mut rng = rand::thread_rng();
-        let rand_index = rng.gen_range(0..3);
-        return match rand_index {
+        let rand_index = rng.gen_range(0..4);
+        match rand_index {
             0 => Self::Green,
             1 => Self::Red,
-            _ => Self::Skin,
-        };
+            2 => Self::Skin,
+            _ => Self::Ranged,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            EnemyType::Green => Color::rgb(0.3, 0.9, 0.3),
+            EnemyType::Red => Color::rgb(0.9, 0.2, 0.2),
+            EnemyType::Skin => Color::rgb(0.9, 0.75, 0.6),
+            EnemyType::Ranged => Color::rgb(0.7, 0.4, 0.9),
+            EnemyType::Boss => Color::ORANGE_RED,
+        }
+    }
+
+    /// How strongly this type steers away from nearby enemies.
+    pub fn separation_weight(&self) -> f32 {
+        match self {
+            EnemyType::Green => GREEN_SEPARATION_WEIGHT,
+            EnemyType::Red => RED_SEPARATION_WEIGHT,
+            EnemyType::Skin => SKIN_SEPARATION_WEIGHT,
+            EnemyType::Ranged => RANGED_SEPARATION_WEIGHT,
+            EnemyType::Boss => BOSS_SEPARATION_WEIGHT,
+        }
     }
 
     pub fn get_base_sprite_index(&self) -> usize {
@@ -48,6 +77,44 @@ impl EnemyType {
             EnemyType::Green => 8,
             EnemyType::Red => 12,
             EnemyType::Skin => 20,
+            EnemyType::Ranged => 0,
+            EnemyType::Boss => 4,
+        }
+    }
+
+    pub fn max_health(&self) -> f32 {
+        match self {
+            EnemyType::Boss => BOSS_HEALTH,
+            _ => ENEMY_HEALTH,
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        match self {
+            EnemyType::Boss => SPRITE_SCALE_FACTOR * BOSS_SCALE_MULTIPLIER,
+            _ => SPRITE_SCALE_FACTOR,
+        }
+    }
+
+    /// How close a player bullet has to pass to hit this type.
+    pub fn hit_radius(&self) -> f32 {
+        match self {
+            EnemyType::Boss => BOSS_HIT_RADIUS,
+            _ => ENEMY_HIT_RADIUS,
+        }
+    }
+
+    pub fn is_ranged(&self) -> bool {
+        matches!(self, EnemyType::Ranged)
+    }
+
+    /// Distance at which this type stops to attack the player or the castle.
+    pub fn attack_range(&self, player: bool) -> f32 {
+        match (self.is_ranged(), player) {
+            (true, _) => RANGED_ENEMY_STANDOFF,
+            (false, true) if *self == EnemyType::Boss => BOSS_PLAYER_ATTACK_RANGE,
+            (false, true) => ENEMY_PLAYER_ATTACK_RANGE,
+            (false, false) => ENEMY_CASTLE_ATTACK_RANGE,
         }
     }
 }
@@ -61,121 +128,415 @@ impl Plugin for EnemyPlagin {
             (
                 spawn_enemies.run_if(on_timer(Duration::from_secs_f32(ENEMY_SPAWN_INTERVAL))),
                 update_enemy_transform,
+                handle_ranged_enemy_attacks,
                 despawn_dead_enemies,
             )
-                .run_if(in_state(GameState::InGame)),
+                .run_if(playing),
         );
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn despawn_dead_enemies(
     mut commands: Commands,
-    enemy_query: Query<(&Enemy, Entity, &Transform), With<Enemy>>,
+    enemy_query: Query<
+        (&Transform, &EnemyType, Option<&Boss>, Option<&EliteAffixes>),
+        With<Enemy>,
+    >,
     handle: Res<GlobalTextureAtlas>,
+    mut death_events: EventReader<DeathEvent>,
+    mut sfx_events: EventWriter<SfxEvent>,
 ) {
-    if enemy_query.is_empty() {
-        return;
-    }
+    for event in death_events.read() {
+        let Ok((transform, enemy_type, boss, affixes)) = enemy_query.get(event.entity) else {
+            continue;
+        };
 
-    for (enemy, entity, transform) in enemy_query.iter() {
-        if enemy.health <= 0.0 {
-            commands.entity(entity).despawn();
-
-            commands.spawn((
-                SpriteSheetBundle {
-                    texture: handle.coin_image.clone().unwrap(),
-                    atlas: TextureAtlas {
-                        layout: handle.coin_layout.clone().unwrap(),
-                        index: 0,
-                    },
-                    transform: Transform::from_translation(vec3(
-                        transform.translation.x,
-                        transform.translation.y,
-                        -1.,
-                    ))
-                    .with_scale(Vec3::splat(COIN_SPRITE_SCALE_FACTOR)),
-                    ..default()
-                },
-                Gold,
-                AnimationTimer(Timer::from_seconds(0.08, TimerMode::Repeating)),
-                GameEntity,
-            ));
+        let pos = transform.translation.truncate();
+        commands.entity(event.entity).despawn();
+        sfx_events.send(SfxEvent::at(Sfx::EnemyDeath, pos));
+
+        if affixes.is_some_and(|affixes| affixes.has(EliteAffix::Splitting)) {
+            spawn_split_enemies(&mut commands, &handle, *enemy_type, pos);
+        }
+
+        if boss.is_none() {
+            spawn_coin(&mut commands, &handle, pos);
+            continue;
         }
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..BOSS_GOLD_REWARD {
+            let offset = vec2(
+                rng.gen_range(-BOSS_REWARD_SCATTER..BOSS_REWARD_SCATTER),
+                rng.gen_range(-BOSS_REWARD_SCATTER..BOSS_REWARD_SCATTER),
+            );
+            spawn_coin(&mut commands, &handle, pos + offset);
+        }
+
+        commands.spawn((
+            SpriteSheetBundle {
+                texture: handle.crystal_image.clone().unwrap(),
+                atlas: TextureAtlas {
+                    layout: handle.crystal_layout.clone().unwrap(),
+                    index: 0,
+                },
+                transform: Transform::from_translation(pos.extend(-1.))
+                    .with_scale(Vec3::splat(CRYSTAL_SPRITE_SCALE_FACTOR)),
+                ..default()
+            },
+            Gold(BOSS_CRYSTAL_VALUE),
+            GameEntity,
+        ));
+    }
+}
+
+fn spawn_split_enemies(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    enemy_type: EnemyType,
+    pos: Vec2,
+) {
+    let mut rng = rand::thread_rng();
+    for _ in 0..SPLIT_COUNT {
+        let offset = vec2(
+            rng.gen_range(-SPLIT_SCATTER..SPLIT_SCATTER),
+            rng.gen_range(-SPLIT_SCATTER..SPLIT_SCATTER),
+        );
+        spawn_enemy(commands, handle, enemy_type, pos + offset, SPLIT_SCALE);
     }
 }
 
+fn spawn_coin(commands: &mut Commands, handle: &GlobalTextureAtlas, pos: Vec2) {
+    commands.spawn((
+        SpriteSheetBundle {
+            texture: handle.coin_image.clone().unwrap(),
+            atlas: TextureAtlas {
+                layout: handle.coin_layout.clone().unwrap(),
+                index: 0,
+            },
+            transform: Transform::from_translation(pos.extend(-1.))
+                .with_scale(Vec3::splat(COIN_SPRITE_SCALE_FACTOR)),
+            ..default()
+        },
+        Gold(1.0),
+        AnimationTimer(Timer::from_seconds(0.08, TimerMode::Repeating)),
+        GameEntity,
+    ));
+}
+
+#[allow(clippy::type_complexity)]
 fn update_enemy_transform(
     player_query: Query<&Transform, With<Player>>,
     castle_query: Query<&Transform, With<Castle>>,
     mut enemy_query: Query<
-        (&mut Transform, &EnemyType),
+        (
+            Entity,
+            &mut Transform,
+            &EnemyType,
+            &EnemyAi,
+            Option<&EliteAffixes>,
+            Option<&StatusEffects>,
+        ),
         (With<Enemy>, Without<Castle>, Without<Player>),
     >,
+    obstacles: Res<Obstacles>,
+    flow_field: Res<FlowField>,
+    tree: Res<EnemyKdTree>,
+    scaling: Res<DifficultyScaling>,
 ) {
     if enemy_query.is_empty() || castle_query.is_empty() || player_query.is_empty() {
         return;
     }
 
-    let player_pos = player_query.single().translation;
-    let castle_pos = castle_query.single().translation;
-    for (mut transform, enemy_type) in enemy_query.iter_mut() {
-        let mut dir = (castle_pos - transform.translation).normalize();
-        
-        if enemy_type == &EnemyType::Green {
-            dir = (player_pos - transform.translation).normalize();
-        } 
+    let player_pos = player_query.single().translation.truncate();
+    let castle_pos = castle_query.single().translation.truncate();
+    for (entity, mut transform, enemy_type, ai, affixes, statuses) in enemy_query.iter_mut() {
+        if enemy_type.is_ranged() && ai.state == AiState::Attack {
+            continue;
+        }
+
+        let pos = transform.translation.truncate();
+        let dir = match ai.target(pos, player_pos, castle_pos) {
+            Some(target) => (target - pos).normalize_or_zero(),
+            None => flow_field
+                .direction(pos)
+                .unwrap_or_else(|| (castle_pos - pos).normalize_or_zero()),
+        };
+
+        let separation = separation(&tree, entity, pos) * enemy_type.separation_weight();
+        let dir = (dir + separation).normalize_or_zero().extend(0.);
+
+        let speed_multiplier = affixes.map_or(1.0, EliteAffixes::speed_multiplier)
+            * statuses.map_or(1.0, StatusEffects::speed_multiplier)
+            * scaling.speed;
+        transform.translation += dir * ENEMY_SPEED * ai.speed_factor() * speed_multiplier;
+        let pos = obstacles.resolve(transform.translation.truncate(), ENEMY_COLLISION_RADIUS);
+        transform.translation.x = pos.x;
+        transform.translation.y = pos.y;
+    }
+}
+
+/// Boids-style push away from nearby enemies, stronger the closer they are. Only the
+/// nearest `ENEMY_SEPARATION_MAX_NEIGHBOURS` count, so a dense crowd can't add up to a huge
+/// shove. They are picked from a radius query because kd-tree's `nearests` can miss
+/// neighbours standing exactly on the query point.
+fn separation(tree: &EnemyKdTree, entity: Entity, pos: Vec2) -> Vec2 {
+    let mut neighbours: Vec<(f32, &Collidable)> = tree
+        .0
+        .within_radius(&[pos.x, pos.y], ENEMY_SEPARATION_RADIUS)
+        .into_iter()
+        .filter(|neighbour| neighbour.entity != entity)
+        .map(|neighbour| (neighbour.pos.distance(pos), neighbour))
+        .collect();
+    if neighbours.len() > ENEMY_SEPARATION_MAX_NEIGHBOURS {
+        neighbours
+            .select_nth_unstable_by(ENEMY_SEPARATION_MAX_NEIGHBOURS, |a, b| a.0.total_cmp(&b.0));
+        neighbours.truncate(ENEMY_SEPARATION_MAX_NEIGHBOURS);
+    }
+
+    neighbours
+        .into_iter()
+        .fold(Vec2::ZERO, |acc, (distance, neighbour)| {
+            if distance <= f32::EPSILON {
+                return acc + stacked_push(entity, neighbour.entity);
+            }
 
-        transform.translation += dir * ENEMY_SPEED;
+            let offset = pos - neighbour.pos;
+            acc + offset / distance * (1.0 - distance / ENEMY_SEPARATION_RADIUS)
+        })
+}
+
+/// Full-strength push for two enemies on exactly the same point. The direction comes
+/// from the pair's indices, so the two get opposite pushes and stay consistent per frame.
+fn stacked_push(entity: Entity, other: Entity) -> Vec2 {
+    let (low, high) = if entity.index() < other.index() {
+        (entity.index(), other.index())
+    } else {
+        (other.index(), entity.index())
+    };
+    let dir = Vec2::from_angle(low.wrapping_mul(31).wrapping_add(high) as f32);
+
+    if entity.index() < other.index() {
+        dir
+    } else {
+        -dir
     }
 }
 
+/// Hands this tick's spawn budget to a random portal, which telegraphs before releasing it.
 fn spawn_enemies(
-    mut commands: Commands,
-    handle: Res<GlobalTextureAtlas>,
-    player_query: Query<&Transform, With<Player>>,
-    enemy_query: Query<&Transform, (With<Enemy>, Without<Player>)>,
+    scaling: Res<DifficultyScaling>,
+    director: Res<Director>,
+    enemy_query: Query<(), With<Enemy>>,
+    mut portal_query: Query<&mut Portal>,
 ) {
-    let num_enemies = enemy_query.iter().len();
-    let enemy_spawn_count = (MAX_NUM_ENEMIES - num_enemies).min(SPAWN_RATE_PER_SECOND);
+    let queued: usize = portal_query.iter().map(|portal| portal.pending).sum();
+    let enemy_spawn_count = scaling
+        .max_enemies()
+        .saturating_sub(enemy_query.iter().len() + queued)
+        .min((scaling.spawn_rate() as f32 * director.spawn_rate_multiplier()).round() as usize);
 
-    if enemy_spawn_count >= MAX_NUM_ENEMIES || player_query.is_empty() {
+    if enemy_spawn_count == 0 {
         return;
     }
 
-    let player_pos = player_query.single().translation.truncate();
-    for _ in 0..enemy_spawn_count {
-        let (x, y) = get_random_position_around(player_pos);
-        let enemy_type = EnemyType::get_rand_enemy();
+    let mut portals = portal_query.iter_mut().collect::<Vec<_>>();
+    if portals.is_empty() {
+        return;
+    }
 
-        commands.spawn((
-            SpriteSheetBundle {
-                texture: handle.image.clone().unwrap(),
-                atlas: TextureAtlas {
-                    layout: handle.layout.clone().unwrap(),
-                    index: enemy_type.get_base_sprite_index(),
-                },
-                transform: Transform::from_translation(vec3(x, y, 1.))
-                    .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+    let index = rand::thread_rng().gen_range(0..portals.len());
+    portals[index].queue(enemy_spawn_count);
+}
+
+/// Spawns a random non-boss enemy, rolling `elite_chance` for elite affixes.
+pub fn spawn_random_enemy(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    pos: Vec2,
+    elite_chance: f32,
+) {
+    let enemy_type = EnemyType::get_rand_enemy();
+    let enemy = spawn_enemy(commands, handle, enemy_type, pos, 1.0);
+    if let Some(affixes) = EliteAffixes::roll(elite_chance) {
+        if affixes.has(EliteAffix::Armored) {
+            commands
+                .entity(enemy)
+                .insert(enemy_type.resistances().hardened(ARMORED_DAMAGE_REDUCTION));
+        }
+        commands.entity(enemy).insert((
+            Sprite {
+                color: affixes.tint(),
                 ..default()
             },
-            Enemy::default(),
-            enemy_type,
-            AnimationTimer(Timer::from_seconds(0.08, TimerMode::Repeating)),
-            GameEntity,
+            affixes,
         ));
     }
 }
 
-fn get_random_position_around(pos: Vec2) -> (f32, f32) {
-    let mut rng = rand::thread_rng();
-    let angle = rng.gen_range(0.0..PI * 2.0);
-    let dist = rng.gen_range(1000.0..5000.0);
+/// Spawns an enemy whose sprite and max health are multiplied by `size`.
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    enemy_type: EnemyType,
+    pos: Vec2,
+    size: f32,
+) -> Entity {
+    let is_ranged = enemy_type.is_ranged();
+
+    let mut enemy = commands.spawn((
+        SpriteSheetBundle {
+            texture: handle.image.clone().unwrap(),
+            atlas: TextureAtlas {
+                layout: handle.layout.clone().unwrap(),
+                index: enemy_type.get_base_sprite_index(),
+            },
+            transform: Transform::from_translation(pos.extend(1.))
+                .with_scale(Vec3::splat(enemy_type.scale() * size)),
+            ..default()
+        },
+        Enemy,
+        Health::new(enemy_type.max_health() * size),
+        enemy_type,
+        enemy_type.resistances(),
+        EnemyAi::new(pos),
+        AnimationTimer(Timer::from_seconds(0.08, TimerMode::Repeating)),
+        GameEntity,
+    ));
+    if is_ranged {
+        enemy.insert(RangedAttack(Timer::from_seconds(
+            RANGED_ENEMY_FIRE_INTERVAL,
+            TimerMode::Repeating,
+        )));
+    }
+
+    enemy.id()
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_ranged_enemy_attacks(
+    mut commands: Commands,
+    time: Res<Time>,
+    handle: Res<GlobalTextureAtlas>,
+    player_query: Query<&Transform, With<Player>>,
+    castle_query: Query<&Transform, With<Castle>>,
+    mut enemy_query: Query<
+        (&Transform, &EnemyAi, &mut RangedAttack, Option<&StatusEffects>),
+        (With<Enemy>, Without<Player>, Without<Castle>),
+    >,
+    mut sfx_events: EventWriter<SfxEvent>,
+) {
+    let (Ok(player_transform), Ok(castle_transform)) =
+        (player_query.get_single(), castle_query.get_single())
+    else {
+        return;
+    };
 
-    let offset_x = angle.cos() * dist;
-    let offset_y = angle.sin() * dist;
+    for (transform, ai, mut attack, statuses) in enemy_query.iter_mut() {
+        if statuses.is_some_and(|statuses| statuses.has(StatusKind::Freeze)) {
+            continue;
+        }
 
-    let random_x = pos.x + offset_x;
-    let random_y = pos.y + offset_y;
+        attack.0.tick(time.delta());
+        if ai.state != AiState::Attack || !attack.0.just_finished() {
+            continue;
+        }
+
+        let target = if ai.attacking_player {
+            player_transform.translation
+        } else {
+            castle_transform.translation
+        };
+        let pos = transform.translation;
+        let dir = (target - pos).truncate().normalize_or_zero().extend(0.);
+
+        spawn_enemy_bullet(&mut commands, &handle, pos, dir);
+        sfx_events.send(SfxEvent::at(Sfx::Shoot, pos.truncate()));
+    }
+}
 
-    return (random_x, random_y);
+pub fn spawn_enemy_bullet(commands: &mut Commands, handle: &GlobalTextureAtlas, pos: Vec3, dir: Vec3) {
+    commands.spawn((
+        SpriteSheetBundle {
+            texture: handle.image.clone().unwrap(),
+            atlas: TextureAtlas {
+                layout: handle.layout.clone().unwrap(),
+                index: ENEMY_BULLET_SPRITE_INDEX,
+            },
+            transform: Transform::from_translation(vec3(pos.x, pos.y, 1.0))
+                .with_rotation(Quat::from_rotation_z(dir.y.atan2(dir.x)))
+                .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+            ..default()
+        },
+        Bullet,
+        Faction::Enemy,
+        Damage::physical(ENEMY_BULLET_DAMAGE),
+        BulletDirection(dir),
+        SpawnInstant(Instant::now()),
+        GameEntity,
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use kd_tree::KdTree;
+
+    use super::*;
+
+    fn tree(points: &[(u32, Vec2)]) -> EnemyKdTree {
+        EnemyKdTree(KdTree::build_by_ordered_float(
+            points
+                .iter()
+                .map(|(index, pos)| Collidable {
+                    pos: *pos,
+                    entity: Entity::from_raw(*index),
+                })
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn separation_ignores_self_and_distant_enemies() {
+        let tree = tree(&[
+            (0, Vec2::ZERO),
+            (1, vec2(ENEMY_SEPARATION_RADIUS * 2.0, 0.0)),
+        ]);
+        assert_eq!(
+            separation(&tree, Entity::from_raw(0), Vec2::ZERO),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn separation_pushes_away_from_close_enemies() {
+        let tree = tree(&[(0, Vec2::ZERO), (1, vec2(10.0, 0.0))]);
+        let push = separation(&tree, Entity::from_raw(0), Vec2::ZERO);
+        assert!(push.x < 0.0 && push.y.abs() < 1e-4);
+    }
+
+    #[test]
+    fn separation_only_counts_the_nearest_neighbours() {
+        let mut points = vec![(0, Vec2::ZERO)];
+        for index in 1..=ENEMY_SEPARATION_MAX_NEIGHBOURS as u32 + 5 {
+            points.push((index, vec2(10.0, 0.0)));
+        }
+        let push = separation(&tree(&points), Entity::from_raw(0), Vec2::ZERO);
+
+        let single = 1.0 - 10.0 / ENEMY_SEPARATION_RADIUS;
+        let expected = single * ENEMY_SEPARATION_MAX_NEIGHBOURS as f32;
+        assert!((push.length() - expected).abs() < 1e-3);
+    }
+
+    #[test]
+    fn stacked_enemies_split_in_opposite_directions() {
+        let (a, b) = (Entity::from_raw(3), Entity::from_raw(8));
+        let tree = tree(&[(3, Vec2::ONE), (8, Vec2::ONE)]);
+
+        let push_a = separation(&tree, a, Vec2::ONE);
+        let push_b = separation(&tree, b, Vec2::ONE);
+        assert!((push_a.length() - 1.0).abs() < 1e-4);
+        assert!((push_a + push_b).length() < 1e-4);
+        assert_eq!(stacked_push(a, b), separation(&tree, a, Vec2::ONE));
+    }
 }
@@ -0,0 +1,336 @@
+use bevy::prelude::*;
+
+use crate::ai::EnemyShotEvent;
+use crate::animation::AnimationTimer;
+use crate::audio::{Sfx, SfxEvent};
+use crate::castle::Castle;
+use crate::collision::EnemyKdTree;
+use crate::damage::{Damage, DamageEvent, DamageKind};
+use crate::enemy::{Enemy, EnemyType};
+use crate::gun::{Gun, GunAmmo, WeaponStats};
+use crate::input::{ActionState, InputAction};
+use crate::obstacle::Obstacles;
+use crate::portal::Portal;
+use crate::state::playing;
+use crate::status::{ApplyStatusEvent, OnHitStatus, StatusKind};
+use crate::world::GameEntity;
+use crate::*;
+
+pub struct ExplosionPlugin;
+
+/// Secondary weapon on the gun that fires explosive rockets.
+#[derive(Component)]
+pub struct RocketLauncher {
+    pub cooldown: Timer,
+}
+
+/// Slow projectile that detonates on impact or when its fuse runs out.
+#[derive(Component)]
+pub struct Rocket {
+    direction: Vec2,
+    fuse: Timer,
+}
+
+/// Pushes an entity away from a blast, fading out over its timer.
+#[derive(Component)]
+pub struct Knockback {
+    velocity: Vec2,
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct Explosion;
+
+/// A player blast at `pos`, hurting enemies and portals within `radius`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ExplosionEvent {
+    pub pos: Vec2,
+    pub radius: f32,
+    pub damage: Damage,
+    pub status: Option<StatusKind>,
+}
+
+impl Default for RocketLauncher {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(ROCKET_COOLDOWN_SECS, TimerMode::Once);
+        cooldown.tick(cooldown.duration());
+
+        Self { cooldown }
+    }
+}
+
+impl Plugin for ExplosionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExplosionEvent>().add_systems(
+            Update,
+            (
+                handle_rocket_input,
+                update_rockets,
+                handle_explosions,
+                apply_knockback,
+                animate_explosions,
+            )
+                .chain()
+                .run_if(playing),
+        );
+    }
+}
+
+/// Damage share at `distance` from the centre, from full down to `EXPLOSION_MIN_FALLOFF` at the edge.
+fn falloff(distance: f32, radius: f32) -> f32 {
+    1.0 - (1.0 - EXPLOSION_MIN_FALLOFF) * (distance / radius).clamp(0.0, 1.0)
+}
+
+fn handle_rocket_input(
+    mut commands: Commands,
+    mut gun_query: Query<(&Transform, &mut RocketLauncher, &GunAmmo, &WeaponStats), With<Gun>>,
+    time: Res<Time>,
+    handle: Res<GlobalTextureAtlas>,
+    actions: Res<ActionState>,
+    mut sfx_events: EventWriter<SfxEvent>,
+) {
+    let Ok((transform, mut launcher, ammo, stats)) = gun_query.get_single_mut() else {
+        return;
+    };
+    launcher.cooldown.tick(time.delta());
+
+    if !actions.just_pressed(InputAction::FireRocket) || !launcher.cooldown.finished() {
+        return;
+    }
+    launcher.cooldown.reset();
+    sfx_events.send(SfxEvent::new(Sfx::Shoot));
+
+    let direction = transform.local_x().truncate();
+    let mut damage = stats.roll(ammo.0.map_or(DamageKind::Physical, DamageKind::from));
+    damage.amount *= ROCKET_DAMAGE_MULTIPLIER;
+
+    let mut rocket = commands.spawn((
+        SpriteSheetBundle {
+            texture: handle.image.clone().unwrap(),
+            atlas: TextureAtlas {
+                layout: handle.layout.clone().unwrap(),
+                index: 16,
+            },
+            sprite: Sprite {
+                color: ammo.0.map_or(Color::ORANGE, |kind| kind.tint()),
+                ..default()
+            },
+            transform: Transform::from_translation(transform.translation.truncate().extend(10.0))
+                .with_scale(Vec3::splat(ROCKET_SPRITE_SCALE_FACTOR)),
+            ..default()
+        },
+        Rocket {
+            direction,
+            fuse: Timer::from_seconds(ROCKET_FUSE_SECS, TimerMode::Once),
+        },
+        damage,
+        GameEntity,
+    ));
+    if let Some(kind) = ammo.0 {
+        rocket.insert(OnHitStatus(kind));
+    }
+}
+
+/// Moves rockets and detonates them on enemies, portals, obstacles or when the fuse runs out.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_rockets(
+    mut commands: Commands,
+    mut rocket_query: Query<(
+        Entity,
+        &mut Transform,
+        &mut Rocket,
+        &Damage,
+        Option<&OnHitStatus>,
+    )>,
+    enemy_query: Query<&EnemyType, With<Enemy>>,
+    portal_query: Query<&Transform, (With<Portal>, Without<Rocket>)>,
+    tree: Res<EnemyKdTree>,
+    obstacles: Res<Obstacles>,
+    time: Res<Time>,
+    mut explosion_events: EventWriter<ExplosionEvent>,
+) {
+    for (entity, mut transform, mut rocket, damage, on_hit) in rocket_query.iter_mut() {
+        rocket.fuse.tick(time.delta());
+        transform.translation += (rocket.direction * ROCKET_SPEED).extend(0.0);
+
+        let pos = transform.translation.truncate();
+        let hit_enemy = tree
+            .0
+            .within_radius(&[pos.x, pos.y], BOSS_HIT_RADIUS)
+            .into_iter()
+            .any(|e| {
+                enemy_query
+                    .get(e.entity)
+                    .is_ok_and(|enemy_type| e.pos.distance(pos) <= enemy_type.hit_radius())
+            });
+        let hit_portal = portal_query
+            .iter()
+            .any(|portal| portal.translation.truncate().distance(pos) <= PORTAL_HIT_RADIUS);
+
+        if hit_enemy || hit_portal || obstacles.contains(pos) || rocket.fuse.finished() {
+            explosion_events.send(ExplosionEvent {
+                pos,
+                radius: EXPLOSION_RADIUS,
+                damage: *damage,
+                status: on_hit.map(|OnHitStatus(kind)| *kind),
+            });
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Blasts hurt enemies, portals and, weakened, the castle but never the player.
+#[allow(clippy::too_many_arguments)]
+fn handle_explosions(
+    mut commands: Commands,
+    mut explosion_events: EventReader<ExplosionEvent>,
+    tree: Res<EnemyKdTree>,
+    enemy_query: Query<&EnemyType, With<Enemy>>,
+    portal_query: Query<(Entity, &Transform), With<Portal>>,
+    castle_query: Query<(Entity, &Transform), With<Castle>>,
+    handle: Res<GlobalTextureAtlas>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut status_events: EventWriter<ApplyStatusEvent>,
+    mut shot_events: EventWriter<EnemyShotEvent>,
+    mut sfx_events: EventWriter<SfxEvent>,
+) {
+    for event in explosion_events.read() {
+        sfx_events.send(SfxEvent::at(Sfx::EnemyDeath, event.pos));
+        commands.spawn((
+            SpriteSheetBundle {
+                texture: handle.explosion_image.clone().unwrap(),
+                atlas: TextureAtlas {
+                    layout: handle.explosion_layout.clone().unwrap(),
+                    index: 0,
+                },
+                sprite: Sprite {
+                    color: event.status.map_or(Color::WHITE, |kind| kind.tint()),
+                    ..default()
+                },
+                transform: Transform::from_translation(event.pos.extend(12.0))
+                    .with_scale(Vec3::splat(event.radius * 2.0 / EXPLOSION_TILE_W as f32)),
+                ..default()
+            },
+            Explosion,
+            AnimationTimer(Timer::from_seconds(
+                EXPLOSION_FRAME_SECS,
+                TimerMode::Repeating,
+            )),
+            GameEntity,
+        ));
+
+        let scaled = |share: f32| Damage {
+            amount: event.damage.amount * share,
+            ..event.damage
+        };
+
+        for e in tree
+            .0
+            .within_radius(&[event.pos.x, event.pos.y], event.radius)
+        {
+            let Ok(enemy_type) = enemy_query.get(e.entity) else {
+                continue;
+            };
+            let offset = e.pos - event.pos;
+            let share = falloff(offset.length(), event.radius);
+
+            damage_events.send(DamageEvent::new(e.entity, None, scaled(share)));
+            shot_events.send(EnemyShotEvent(e.entity));
+            if let Some(kind) = event.status {
+                status_events.send(ApplyStatusEvent {
+                    target: e.entity,
+                    kind,
+                });
+            }
+
+            let resistance = match enemy_type {
+                EnemyType::Boss => BOSS_KNOCKBACK_FACTOR,
+                _ => 1.0,
+            };
+            commands.entity(e.entity).try_insert(Knockback {
+                velocity: offset.normalize_or_zero()
+                    * EXPLOSION_KNOCKBACK_SPEED
+                    * share
+                    * resistance,
+                timer: Timer::from_seconds(EXPLOSION_KNOCKBACK_TIME_SECS, TimerMode::Once),
+            });
+        }
+
+        for (castle, transform) in castle_query.iter() {
+            let distance = transform.translation.truncate().distance(event.pos);
+            if distance < event.radius + ENEMY_BULLET_CASTLE_HIT_RADIUS {
+                let share = falloff(distance, event.radius + ENEMY_BULLET_CASTLE_HIT_RADIUS)
+                    * EXPLOSION_CASTLE_FRIENDLY_FIRE;
+                damage_events.send(DamageEvent::new(castle, None, scaled(share)));
+            }
+        }
+
+        for (portal, transform) in portal_query.iter() {
+            let distance = transform.translation.truncate().distance(event.pos);
+            if distance < event.radius + PORTAL_HIT_RADIUS {
+                let share = falloff(distance, event.radius + PORTAL_HIT_RADIUS);
+                damage_events.send(DamageEvent::new(portal, None, scaled(share)));
+            }
+        }
+    }
+}
+
+fn apply_knockback(
+    mut commands: Commands,
+    mut knockback_query: Query<(Entity, &mut Transform, &mut Knockback)>,
+    obstacles: Res<Obstacles>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform, mut knockback) in knockback_query.iter_mut() {
+        knockback.timer.tick(time.delta());
+        if knockback.timer.finished() {
+            commands.entity(entity).remove::<Knockback>();
+            continue;
+        }
+
+        let strength = 1.0 - knockback.timer.fraction();
+        let pos =
+            transform.translation.truncate() + knockback.velocity * strength * time.delta_seconds();
+        let pos = obstacles.resolve(pos, ENEMY_COLLISION_RADIUS);
+        transform.translation.x = pos.x.clamp(-WORLD_W, WORLD_W);
+        transform.translation.y = pos.y.clamp(-WORLD_H, WORLD_H);
+    }
+}
+
+/// Steps through the blast frames and removes the sprite after the last one.
+fn animate_explosions(
+    mut commands: Commands,
+    mut explosion_query: Query<(Entity, &mut TextureAtlas, &AnimationTimer), With<Explosion>>,
+) {
+    for (entity, mut atlas, timer) in explosion_query.iter_mut() {
+        if !timer.just_finished() {
+            continue;
+        }
+        if atlas.index + 1 >= EXPLOSION_SPRITE_SHEET_W * EXPLOSION_SPRITE_SHEET_H {
+            commands.entity(entity).despawn();
+        } else {
+            atlas.index += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falloff_goes_from_full_to_min_at_the_edge() {
+        assert_eq!(falloff(0.0, EXPLOSION_RADIUS), 1.0);
+        assert!((falloff(EXPLOSION_RADIUS, EXPLOSION_RADIUS) - EXPLOSION_MIN_FALLOFF).abs() < 1e-4);
+        let half = falloff(EXPLOSION_RADIUS / 2.0, EXPLOSION_RADIUS);
+        assert!(half < 1.0 && half > EXPLOSION_MIN_FALLOFF);
+    }
+
+    #[test]
+    fn falloff_is_clamped_outside_the_radius() {
+        assert_eq!(
+            falloff(EXPLOSION_RADIUS * 3.0, EXPLOSION_RADIUS),
+            falloff(EXPLOSION_RADIUS, EXPLOSION_RADIUS)
+        );
+    }
+}
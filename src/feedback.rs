@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::castle::Castle;
+use crate::damage::DamageDealtEvent;
+use crate::player::Player;
+use crate::state::playing;
+use crate::world::GameEntity;
+use crate::*;
+
+pub struct FeedbackPlugin;
+
+/// Floating damage number that rises and fades out over its lifetime.
+#[derive(Component)]
+struct DamageNumber {
+    timer: Timer,
+    velocity: Vec2,
+}
+
+impl Plugin for FeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_damage_numbers, update_damage_numbers).run_if(playing),
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn spawn_damage_numbers(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut events: EventReader<DamageDealtEvent>,
+    target_query: Query<&Transform>,
+    friendly_query: Query<(), Or<(With<Player>, With<Castle>)>>,
+) {
+    let mut rng = rand::thread_rng();
+    for event in events.read() {
+        if event.amount < DAMAGE_NUMBER_MIN_AMOUNT && !event.crit {
+            continue;
+        }
+        let Ok(transform) = target_query.get(event.target) else {
+            continue;
+        };
+
+        let (label, font_size, color) = if event.crit {
+            (
+                format!("{:.0}!", event.amount),
+                DAMAGE_NUMBER_CRIT_FONT_SIZE,
+                Color::YELLOW,
+            )
+        } else if friendly_query.contains(event.target) {
+            (
+                format!("{:.0}", event.amount),
+                DAMAGE_NUMBER_FONT_SIZE,
+                Color::RED,
+            )
+        } else {
+            (
+                format!("{:.0}", event.amount),
+                DAMAGE_NUMBER_FONT_SIZE,
+                Color::WHITE,
+            )
+        };
+
+        let pos = transform.translation.truncate();
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    label,
+                    TextStyle {
+                        font: asset_server.load("monogram.ttf"),
+                        font_size,
+                        color,
+                    },
+                ),
+                transform: Transform::from_translation(pos.extend(DAMAGE_NUMBER_Z)),
+                ..default()
+            },
+            DamageNumber {
+                timer: Timer::from_seconds(DAMAGE_NUMBER_TIME_SECS, TimerMode::Once),
+                velocity: Vec2::new(
+                    rng.gen_range(-0.5..0.5) * DAMAGE_NUMBER_RISE_SPEED,
+                    DAMAGE_NUMBER_RISE_SPEED,
+                ),
+            },
+            GameEntity,
+        ));
+    }
+}
+
+fn update_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &mut Text, &mut DamageNumber)>,
+) {
+    for (entity, mut transform, mut text, mut number) in query.iter_mut() {
+        number.timer.tick(time.delta());
+        if number.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation += (number.velocity * time.delta_seconds()).extend(0.0);
+        let alpha = 1.0 - number.timer.fraction();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(alpha);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use bevy::input::gamepad::GamepadConnectionEvent;
+use bevy::prelude::*;
+use bevy::window::CursorMoved;
+
+use crate::state::playing;
+use crate::*;
+
+pub struct GamepadInputPlugin;
+
+#[derive(Resource, Default)]
+pub struct ActiveGamepad(pub Option<Gamepad>);
+
+#[derive(Resource, Default)]
+pub struct GamepadAim(pub Option<Vec2>);
+
+impl Plugin for GamepadInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ActiveGamepad::default())
+            .insert_resource(GamepadAim::default())
+            .add_systems(Update, track_active_gamepad)
+            .add_systems(
+                Update,
+                update_gamepad_aim.run_if(playing),
+            );
+    }
+}
+
+impl ActiveGamepad {
+    pub fn left_stick(&self, axes: &Axis<GamepadAxis>) -> Vec2 {
+        self.stick(axes, GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY)
+    }
+
+    pub fn right_stick(&self, axes: &Axis<GamepadAxis>) -> Vec2 {
+        self.stick(axes, GamepadAxisType::RightStickX, GamepadAxisType::RightStickY)
+    }
+
+    pub fn pressed(&self, buttons: &ButtonInput<GamepadButton>, button: GamepadButtonType) -> bool {
+        match self.0 {
+            Some(gamepad) => buttons.pressed(GamepadButton::new(gamepad, button)),
+            None => false,
+        }
+    }
+
+    pub fn just_pressed(
+        &self,
+        buttons: &ButtonInput<GamepadButton>,
+        button: GamepadButtonType,
+    ) -> bool {
+        match self.0 {
+            Some(gamepad) => buttons.just_pressed(GamepadButton::new(gamepad, button)),
+            None => false,
+        }
+    }
+
+    fn stick(&self, axes: &Axis<GamepadAxis>, x: GamepadAxisType, y: GamepadAxisType) -> Vec2 {
+        let Some(gamepad) = self.0 else {
+            return Vec2::ZERO;
+        };
+
+        let value = Vec2::new(
+            axes.get(GamepadAxis::new(gamepad, x)).unwrap_or(0.),
+            axes.get(GamepadAxis::new(gamepad, y)).unwrap_or(0.),
+        );
+
+        if value.length() < GAMEPAD_STICK_DEADZONE {
+            return Vec2::ZERO;
+        }
+
+        value.clamp_length_max(1.)
+    }
+}
+
+fn track_active_gamepad(
+    mut active_gamepad: ResMut<ActiveGamepad>,
+    mut events: EventReader<GamepadConnectionEvent>,
+    gamepads: Res<Gamepads>,
+) {
+    for event in events.read() {
+        if event.connected() && active_gamepad.0.is_none() {
+            active_gamepad.0 = Some(event.gamepad);
+        } else if event.disconnected() && active_gamepad.0 == Some(event.gamepad) {
+            active_gamepad.0 = gamepads.iter().find(|gamepad| *gamepad != event.gamepad);
+        }
+    }
+}
+
+fn update_gamepad_aim(
+    active_gamepad: Res<ActiveGamepad>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut aim: ResMut<GamepadAim>,
+    mut cursor_events: EventReader<CursorMoved>,
+) {
+    if cursor_events.read().count() > 0 {
+        aim.0 = None;
+    }
+
+    let stick = active_gamepad.right_stick(&axes);
+    if stick != Vec2::ZERO {
+        aim.0 = Some(stick.normalize());
+    }
+}
@@ -1,15 +1,17 @@
 use bevy::prelude::*;
 
+use crate::audio::{Sfx, SfxEvent};
 use crate::player::{GoldCount, Player};
-use crate::state::GameState;
+use crate::state::playing;
 
+/// A pickup worth the given amount of gold.
 #[derive(Component)]
-pub struct Gold;
+pub struct Gold(pub f32);
 
 pub struct GoldPlugin;
 
 #[derive(Event)]
-pub struct PlayerGoldCollisionEvent;
+pub struct PlayerGoldCollisionEvent(pub f32);
 
 impl Plugin for GoldPlugin {
     fn build(&self, app: &mut App) {
@@ -18,7 +20,7 @@ impl Plugin for GoldPlugin {
             Update,
             (
                   handle_player_gold_collision_events,
-            ).run_if(in_state(GameState::InGame))
+            ).run_if(playing)
       );
     }
 }
@@ -26,14 +28,16 @@ impl Plugin for GoldPlugin {
 fn handle_player_gold_collision_events(
       mut player_query: Query<&mut GoldCount, With<Player>>,
       mut events: EventReader<PlayerGoldCollisionEvent>,
+      mut sfx_events: EventWriter<SfxEvent>,
   ) {
       if player_query.is_empty() {
           return;
       }
   
       let mut gold = player_query.single_mut();
-      for _ in events.read() {
-            gold.0 += 1.;
+      for PlayerGoldCollisionEvent(value) in events.read() {
+            gold.0 += value;
+            sfx_events.send(SfxEvent::new(Sfx::GoldPickup));
       }
   }
   
\ No newline at end of file
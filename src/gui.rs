@@ -1,14 +1,21 @@
 use bevy::app::Plugin;
 use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
 
 use crate::animation::AnimationTimer;
 use crate::castle::Castle;
+use crate::damage::{Health, Shield};
+use crate::difficulty::DifficultyScaling;
+use crate::director::Director;
 use crate::enemy::Enemy;
-use crate::player::{GoldCount, Health, Player};
-use crate::state::GameState;
+use crate::gun::{GunAmmo, WeaponStats};
+use crate::input::{save_keymap, ActionState, InputAction, Keymap, PendingRebind};
+use crate::settings::{save_settings, Settings};
+use crate::player::{GoldCount, Player};
+use crate::state::{GameState, Paused};
 use crate::world::GameEntity;
-use crate::{GlobalTextureAtlas, MENU_SPRITE_SCALE_FACTOR};
+use crate::{GlobalTextureAtlas, BINDING_SLOTS, MENU_TILE_H, MENU_TILE_W, WH, WW};
 
 #[derive(Component)]
 struct DebugText;
@@ -16,28 +23,97 @@ struct DebugText;
 #[derive(Component)]
 struct CoinText;
 
+#[derive(Component)]
+struct WeaponText;
+
 #[derive(Component)]
 pub struct MenuBG;
 
 #[derive(Component)]
 struct MainMenuItem;
 #[derive(Component)]
+struct ControlsMenuItem;
+#[derive(Component)]
+struct SettingsMenuItem;
+#[derive(Component)]
+struct PauseMenuItem;
+#[derive(Component)]
 struct MenuImage;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum MenuButton {
+    Play,
+    Difficulty,
+    Controls,
+    Settings,
+    Rebind(InputAction, usize),
+    ResetControls,
+    WindowMode,
+    Resolution,
+    VSync,
+    MinZoom,
+    MaxZoom,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Director,
+    Back,
+    Resume,
+    QuitToMenu,
+}
+
+#[derive(Event)]
+struct MenuButtonEvent(MenuButton);
+
+#[derive(Resource, Default)]
+struct MenuFocus(Option<Entity>);
 pub struct GuiPlugin;
 
 impl Plugin for GuiPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(FrameTimeDiagnosticsPlugin)
+            .add_event::<MenuButtonEvent>()
+            .insert_resource(MenuFocus::default())
+            .init_resource::<Paused>()
+            .add_systems(Update, update_ui_scale)
             .add_systems(OnEnter(GameState::MainMenu), setup_main_menu)
             .add_systems(OnExit(GameState::MainMenu), despawn_main_menu)
             .add_systems(
                 Update,
-                handle_main_menu_buttons.run_if(in_state(GameState::MainMenu)),
+                (
+                    handle_main_menu_buttons,
+                    handle_menu_navigation,
+                    update_menu_button_sprites,
+                    handle_menu_button_events,
+                    fit_menu_bg_to_camera,
+                )
+                    .run_if(in_menu),
+            )
+            .add_systems(OnEnter(GameState::Controls), setup_controls_menu)
+            .add_systems(OnExit(GameState::Controls), despawn_controls_menu)
+            .add_systems(
+                Update,
+                update_binding_labels.run_if(in_state(GameState::Controls)),
+            )
+            .add_systems(OnEnter(GameState::Settings), setup_settings_menu)
+            .add_systems(OnExit(GameState::Settings), despawn_settings_menu)
+            .add_systems(Update, update_settings_labels.run_if(in_menu))
+            .add_systems(
+                Update,
+                exit_submenu_on_escape
+                    .run_if(in_state(GameState::Controls).or_else(in_state(GameState::Settings))),
+            )
+            .add_systems(
+                Update,
+                (toggle_pause, update_pause_menu)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
             )
+            .add_systems(OnExit(GameState::InGame), reset_pause)
             .add_systems(OnEnter(GameState::InGame), (spawn_debug_text, spawn_res_ui))
             .add_systems(
                 Update,
-                (update_debug_text, update_res_text).run_if(in_state(GameState::InGame)),
+                (update_debug_text, update_res_text, update_weapon_text).run_if(in_state(GameState::InGame)),
             );
     }
 }
@@ -63,7 +139,7 @@ fn spawn_debug_text(mut commands: Commands, asset_server: Res<AssetServer>) {
                 .spawn(NodeBundle {
                     style: Style {
                         width: Val::Px(440.0),
-                        height: Val::Px(165.0),
+                        height: Val::Px(270.0),
                         align_items: AlignItems::Center,
                         flex_direction: FlexDirection::Column,
                         justify_content: JustifyContent::Center,
@@ -82,7 +158,6 @@ fn spawn_debug_text(mut commands: Commands, asset_server: Res<AssetServer>) {
                                 font: asset_server.load("monogram.ttf"),
                                 font_size: 40.0,
                                 color: Color::WHITE,
-                                ..default()
                             },
                         ),
                         DebugText,
@@ -115,7 +190,7 @@ fn spawn_res_ui(
             parent
                 .spawn(NodeBundle {
                     style: Style {
-                        width: Val::Px(1600.),
+                        flex_grow: 1.0,
                         height: Val::Px(50.0),
                         align_items: AlignItems::Center,
                         flex_direction: FlexDirection::Row,
@@ -148,38 +223,75 @@ fn spawn_res_ui(
                                 font: asset_server.load("monogram.ttf"),
                                 font_size: 40.0,
                                 color: Color::WHITE,
-                                ..default()
                             },
                         ),
                         CoinText,
                     ));
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font: asset_server.load("monogram.ttf"),
+                                font_size: 32.0,
+                                color: Color::WHITE,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::left(Val::Px(40.0)),
+                            ..default()
+                        }),
+                        WeaponText,
+                    ));
                 });
         });
 }
 
+#[allow(clippy::too_many_arguments)]
 fn update_debug_text(
     mut query: Query<&mut Text, With<DebugText>>,
     diagnostics: Res<DiagnosticsStore>,
     enemy_query: Query<(), With<Enemy>>,
-    player_query: Query<&Health, With<Player>>,
-    castle_query: Query<&Health, With<Castle>>,
+    player_query: Query<(&Health, Option<&Shield>), With<Player>>,
+    castle_query: Query<(&Health, Option<&Shield>), With<Castle>>,
+    settings: Res<Settings>,
+    scaling: Res<DifficultyScaling>,
+    director: Res<Director>,
+    ammo_query: Query<&GunAmmo>,
 ) {
     if query.is_empty() || player_query.is_empty() || enemy_query.is_empty() || castle_query.is_empty() {
         return;
     }
 
     let num_enemies = enemy_query.iter().count();
-    let player_health = player_query.single().0;
-    let castle_health = castle_query.single().0;
+    let player_health = health_label(player_query.single());
+    let castle_health = health_label(castle_query.single());
     let mut text = query.single_mut();
     if let Some(fps) = diagnostics.get(&FrameTimeDiagnosticsPlugin::FPS) {
         if let Some(value) = fps.smoothed() {
             text.sections[0].value =
-                format!("Fps: {value:.2}\nEnemies: {num_enemies}\nHealth: {player_health}\nCastle_Health: {castle_health}");
+                format!(
+                "Fps: {value:.2}\nEnemies: {num_enemies}\nHealth: {player_health}\nCastle_Health: {castle_health}\nDifficulty: {} x{:.2}\nIntensity: {:.2} ({:+.2})\nAmmo: {}",
+                settings.difficulty.label(),
+                scaling.ramp,
+                director.intensity,
+                director.bias,
+                ammo_query
+                    .iter()
+                    .next()
+                    .and_then(|ammo| ammo.0)
+                    .map_or("Normal", |kind| kind.label()),
+            );
         }
     }
 }
 
+fn health_label((health, shield): (&Health, Option<&Shield>)) -> String {
+    match shield {
+        Some(shield) => format!("{:.0} (+{:.0})", health.current, shield.current),
+        None => format!("{:.0}", health.current),
+    }
+}
+
 fn update_res_text(
     mut query: Query<&mut Text, With<CoinText>>,
     player_query: Query<&GoldCount, With<Player>>,
@@ -194,9 +306,32 @@ fn update_res_text(
     text.sections[0].value = format!(": {player_gold}");
 }
 
+fn update_weapon_text(
+    mut query: Query<&mut Text, With<WeaponText>>,
+    stats_query: Query<&WeaponStats>,
+    keymap: Res<Keymap>,
+) {
+    let (Ok(mut text), Some(stats)) = (query.get_single_mut(), stats_query.iter().next()) else {
+        return;
+    };
+
+    text.sections[0].value = format!(
+        "Dmg {:.0}-{:.0}  Crit {:.0}% x{:.2}  [{}] +Dmg {}g  [{}] +Crit {}g",
+        stats.min_damage,
+        stats.max_damage,
+        stats.crit_chance * 100.0,
+        stats.crit_multiplier,
+        keymap.label(InputAction::UpgradeDamage),
+        stats.damage_upgrade_cost(),
+        keymap.label(InputAction::UpgradeCrit),
+        stats.crit_upgrade_cost(),
+    );
+}
+
 fn setup_main_menu(
     mut commands: Commands,
     handle: Res<GlobalTextureAtlas>,
+    settings: Res<Settings>,
 ) {
     commands
         .spawn((SpriteSheetBundle {
@@ -205,7 +340,6 @@ fn setup_main_menu(
                 layout: handle.menu_layout.clone().unwrap(),
                 index: 0,
             },
-            transform: Transform::from_scale(Vec3::splat(MENU_SPRITE_SCALE_FACTOR)),
             ..default()
         },
         MenuBG,
@@ -220,85 +354,575 @@ fn setup_main_menu(
                 height: Val::Percent(100.0),
                 align_items: AlignItems::Center,
                 justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(20.0),
                 ..default()
             },
             ..default()
         })
+        .with_children(|parent| {
+            spawn_menu_button(parent, &handle, "Play", MenuButton::Play);
+            let label = settings_label(MenuButton::Difficulty, &settings).unwrap_or_default();
+            spawn_menu_button(parent, &handle, &label, MenuButton::Difficulty);
+            spawn_menu_button(parent, &handle, "Controls", MenuButton::Controls);
+            spawn_menu_button(parent, &handle, "Settings", MenuButton::Settings);
+        })
+        .insert(MainMenuItem);
+}
+
+fn setup_controls_menu(
+    mut commands: Commands,
+    handle: Res<GlobalTextureAtlas>,
+    keymap: Res<Keymap>,
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(5.0),
+                    ..default()
+                },
+                background_color: BackgroundColor::from(Color::BLACK.with_a(0.9)),
+                ..default()
+            },
+            ControlsMenuItem,
+        ))
         .with_children(|parent| {
             parent
-                .spawn(ButtonBundle {
+                .spawn(NodeBundle {
                     style: Style {
-                        width: Val::Px(200.0),
-                        height: Val::Px(65.0),
-                        border: UiRect::all(Val::Px(5.0)),
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(40.0),
                         ..default()
                     },
-                    background_color: BackgroundColor::from(Color::BLACK.with_a(0.)),
                     ..default()
-                }).with_children(|parent| {
-                    parent.spawn((
-                        AtlasImageBundle {
-                            style: Style {
-                                width: Val::Px(200.),
-                                height: Val::Px(65.),
-                                justify_content: JustifyContent::Center,
-                                align_items: AlignItems::Center,
-                                ..default()
-                            },
-                            texture_atlas: TextureAtlas {
-                                layout: handle.button_layout.clone().unwrap(),
-                                index: 0,
-                            },
-                            image: UiImage::new(handle.button_image.clone().unwrap()),
-                            ..default()
-                        },
-                        Interaction::default(),
-                        MenuImage,
-                    ))
-                    .with_children(|parent| {
-                        parent.spawn(TextBundle::from_section(
-                            "Play",
-                            TextStyle {
-                                
-                                font_size: 40.0,
-                                color: Color::BLACK,
+                })
+                .with_children(|parent| {
+                    let rows_per_column = InputAction::ALL.len().div_ceil(2);
+                    for column in InputAction::ALL.chunks(rows_per_column) {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    row_gap: Val::Px(5.0),
+                                    ..default()
+                                },
                                 ..default()
-                            },
-                        ));
-                    });
+                            })
+                            .with_children(|parent| {
+                                for action in column {
+                                    spawn_binding_row(parent, &handle, &keymap, *action);
+                                }
+                            });
+                    }
+                });
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(20.0),
+                        margin: UiRect::top(Val::Px(15.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    spawn_menu_button(parent, &handle, "Reset", MenuButton::ResetControls);
+                    spawn_menu_button(parent, &handle, "Back", MenuButton::Back);
                 });
+        });
+}
+
+fn spawn_binding_row(
+    parent: &mut ChildBuilder,
+    handle: &GlobalTextureAtlas,
+    keymap: &Keymap,
+    action: InputAction,
+) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(10.0),
+                ..default()
+            },
+            ..default()
         })
-        .insert(MainMenuItem);
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                text: Text::from_section(
+                    action.label(),
+                    TextStyle {
+                        font_size: 32.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                style: Style {
+                    width: Val::Px(200.0),
+                    ..default()
+                },
+                ..default()
+            });
+            for slot in 0..BINDING_SLOTS {
+                spawn_sized_menu_button(
+                    parent,
+                    handle,
+                    &keymap.slot_label(action, slot),
+                    MenuButton::Rebind(action, slot),
+                    180.0,
+                    55.0,
+                    24.0,
+                );
+            }
+        });
+}
+
+fn setup_settings_menu(
+    mut commands: Commands,
+    handle: Res<GlobalTextureAtlas>,
+    settings: Res<Settings>,
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                },
+                background_color: BackgroundColor::from(Color::BLACK.with_a(0.9)),
+                ..default()
+            },
+            SettingsMenuItem,
+        ))
+        .with_children(|parent| {
+            for button in [
+                MenuButton::WindowMode,
+                MenuButton::Resolution,
+                MenuButton::VSync,
+                MenuButton::MinZoom,
+                MenuButton::MaxZoom,
+                MenuButton::MasterVolume,
+                MenuButton::MusicVolume,
+                MenuButton::SfxVolume,
+                MenuButton::Director,
+            ] {
+                let label = settings_label(button, &settings).unwrap_or_default();
+                spawn_sized_menu_button(parent, &handle, &label, button, 400.0, 65.0, 32.0);
+            }
+
+            spawn_menu_button(parent, &handle, "Back", MenuButton::Back);
+        });
+}
+
+fn settings_label(button: MenuButton, settings: &Settings) -> Option<String> {
+    let label = match button {
+        MenuButton::WindowMode => format!("Window: {}", settings.window_mode.label()),
+        MenuButton::Resolution => {
+            format!("Resolution: {}x{}", settings.resolution.0, settings.resolution.1)
+        }
+        MenuButton::VSync => format!("VSync: {}", if settings.vsync { "On" } else { "Off" }),
+        MenuButton::MinZoom => format!("Min Zoom: {:.1}", settings.min_zoom),
+        MenuButton::MaxZoom => format!("Max Zoom: {:.1}", settings.max_zoom),
+        MenuButton::MasterVolume => {
+            format!("Volume: {:.0}%", settings.master_volume * 100.0)
+        }
+        MenuButton::MusicVolume => format!("Music: {:.0}%", settings.music_volume * 100.0),
+        MenuButton::SfxVolume => format!("Effects: {:.0}%", settings.sfx_volume * 100.0),
+        MenuButton::Director => format!(
+            "Director: {}",
+            if settings.adaptive_director { "On" } else { "Off" }
+        ),
+        MenuButton::Difficulty => settings.difficulty.label().to_string(),
+        _ => return None,
+    };
+
+    Some(label)
+}
+
+fn spawn_menu_button(
+    parent: &mut ChildBuilder,
+    handle: &GlobalTextureAtlas,
+    label: &str,
+    button: MenuButton,
+) {
+    spawn_sized_menu_button(parent, handle, label, button, 200.0, 65.0, 40.0);
 }
 
+fn spawn_sized_menu_button(
+    parent: &mut ChildBuilder,
+    handle: &GlobalTextureAtlas,
+    label: &str,
+    button: MenuButton,
+    width: f32,
+    height: f32,
+    font_size: f32,
+) {
+    parent
+        .spawn(ButtonBundle {
+            style: Style {
+                width: Val::Px(width),
+                height: Val::Px(height),
+                border: UiRect::all(Val::Px(5.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: BackgroundColor::from(Color::BLACK.with_a(0.)),
+            ..default()
+        }).with_children(|parent| {
+            parent.spawn((
+                AtlasImageBundle {
+                    style: Style {
+                        width: Val::Px(width),
+                        height: Val::Px(height),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    texture_atlas: TextureAtlas {
+                        layout: handle.button_layout.clone().unwrap(),
+                        index: 0,
+                    },
+                    image: UiImage::new(handle.button_image.clone().unwrap()),
+                    ..default()
+                },
+                Interaction::default(),
+                MenuImage,
+                button,
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    label,
+                    TextStyle {
+                        font_size,
+                        color: Color::BLACK,
+                        ..default()
+                    },
+                ));
+            });
+        });
+}
+
+#[allow(clippy::type_complexity)]
 fn handle_main_menu_buttons(
-    mut button_query: Query<(&mut TextureAtlas, &Interaction),  (Changed<Interaction>, With<MenuImage>)>,
-    mut next_state: ResMut<NextState<GameState>>,
+    button_query: Query<(&Interaction, &MenuButton), (Changed<Interaction>, With<MenuImage>)>,
+    mut ew: EventWriter<MenuButtonEvent>,
+) {
+    for (interaction, button) in button_query.iter() {
+        if *interaction == Interaction::Pressed {
+            ew.send(MenuButtonEvent(*button));
+        }
+    }
+}
+
+fn handle_menu_navigation(
+    button_query: Query<(Entity, &GlobalTransform, &MenuButton), With<MenuImage>>,
+    actions: Res<ActionState>,
+    pending_rebind: Res<PendingRebind>,
+    mut focus: ResMut<MenuFocus>,
+    mut ew: EventWriter<MenuButtonEvent>,
 ) {
-    if button_query.is_empty() {
+    // The next press belongs to the binding being captured.
+    if button_query.is_empty() || pending_rebind.action.is_some() {
         return;
     }
 
-    for (mut atlas, interaction) in button_query.iter_mut() {
-        match interaction {
-            Interaction::Pressed => {
-                atlas.index = 2;
-                next_state.set(GameState::GameInit);
+    let mut buttons: Vec<(Entity, Vec3, MenuButton)> = button_query
+        .iter()
+        .map(|(entity, transform, button)| (entity, transform.translation(), *button))
+        .collect();
+    buttons.sort_by(|a, b| a.1.y.total_cmp(&b.1.y).then(a.1.x.total_cmp(&b.1.x)));
+
+    let focused_index = focus
+        .0
+        .and_then(|focused| buttons.iter().position(|(entity, _, _)| *entity == focused));
+
+    let next = actions.just_pressed(InputAction::MenuDown);
+    let prev = actions.just_pressed(InputAction::MenuUp);
+
+    let new_index = match (focused_index, next, prev) {
+        (None, true, _) | (None, _, true) => Some(0),
+        (Some(index), true, _) => Some((index + 1) % buttons.len()),
+        (Some(index), _, true) => Some((index + buttons.len() - 1) % buttons.len()),
+        (index, _, _) => index,
+    };
+    focus.0 = new_index.map(|index| buttons[index].0);
+
+    if let Some(index) = new_index {
+        if actions.just_pressed(InputAction::MenuSelect) {
+            ew.send(MenuButtonEvent(buttons[index].2));
+        }
+    }
+}
+
+fn update_menu_button_sprites(
+    mut button_query: Query<(Entity, &mut TextureAtlas, &Interaction), With<MenuImage>>,
+    focus: Res<MenuFocus>,
+) {
+    for (entity, mut atlas, interaction) in button_query.iter_mut() {
+        let index = match interaction {
+            Interaction::Pressed => 2,
+            Interaction::Hovered => 1,
+            Interaction::None if focus.0 == Some(entity) => 1,
+            Interaction::None => 0,
+        };
+
+        if atlas.index != index {
+            atlas.index = index;
+        }
+    }
+}
+
+fn handle_menu_button_events(
+    mut events: EventReader<MenuButtonEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut keymap: ResMut<Keymap>,
+    mut pending_rebind: ResMut<PendingRebind>,
+    mut settings: ResMut<Settings>,
+    mut paused: ResMut<Paused>,
+    state: Res<State<GameState>>,
+) {
+    for event in events.read() {
+        match event.0 {
+            MenuButton::Play => next_state.set(GameState::GameInit),
+            MenuButton::Controls => next_state.set(GameState::Controls),
+            MenuButton::Settings => next_state.set(GameState::Settings),
+            MenuButton::Difficulty => {
+                settings.cycle_difficulty();
+                save_settings(&settings);
             }
-            Interaction::Hovered => {
-                atlas.index = 1;
+            MenuButton::Rebind(action, slot) => pending_rebind.start(action, slot),
+            MenuButton::ResetControls => *keymap = Keymap::default(),
+            MenuButton::WindowMode => settings.cycle_window_mode(),
+            MenuButton::Resolution => settings.cycle_resolution(),
+            MenuButton::VSync => settings.vsync = !settings.vsync,
+            MenuButton::MinZoom => settings.cycle_min_zoom(),
+            MenuButton::MaxZoom => settings.cycle_max_zoom(),
+            MenuButton::MasterVolume => settings.cycle_master_volume(),
+            MenuButton::MusicVolume => settings.cycle_music_volume(),
+            MenuButton::SfxVolume => settings.cycle_sfx_volume(),
+            MenuButton::Director => settings.adaptive_director = !settings.adaptive_director,
+            MenuButton::Back => {
+                if *state.get() == GameState::Controls {
+                    *pending_rebind = PendingRebind::default();
+                    save_keymap(&keymap);
+                } else {
+                    save_settings(&settings);
+                }
+                next_state.set(GameState::MainMenu);
+            }
+            MenuButton::Resume => paused.0 = false,
+            MenuButton::QuitToMenu => next_state.set(GameState::MainMenu),
+        }
+    }
+}
+
+fn update_ui_scale(
+    window_query: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+
+    let scale = (window.width() / WW).min(window.height() / WH);
+    if scale > 0.0 && ui_scale.0 != scale {
+        ui_scale.0 = scale;
+    }
+}
+
+fn fit_menu_bg_to_camera(
+    camera_query: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+    mut menu_query: Query<&mut Transform, (With<MenuBG>, Without<Camera>)>,
+) {
+    if camera_query.is_empty() || menu_query.is_empty() {
+        return;
+    }
+
+    let (camera_transform, projection) = camera_query.single();
+    let view_size = projection.area.size();
+    let scale = (view_size.x / MENU_TILE_W as f32).max(view_size.y / MENU_TILE_H as f32);
+
+    let mut transform = menu_query.single_mut();
+    transform.translation.x = camera_transform.translation.x;
+    transform.translation.y = camera_transform.translation.y;
+    transform.scale = Vec3::splat(scale);
+}
+
+/// Menu screens, plus the pause menu shown over a running game.
+fn in_menu(state: Res<State<GameState>>, paused: Res<Paused>) -> bool {
+    match state.get() {
+        GameState::MainMenu | GameState::Controls | GameState::Settings => true,
+        GameState::InGame => paused.0,
+        _ => false,
+    }
+}
+
+fn toggle_pause(actions: Res<ActionState>, mut paused: ResMut<Paused>) {
+    if actions.just_pressed(InputAction::Pause) {
+        paused.0 = !paused.0;
+    }
+}
+
+/// Shows or hides the pause menu and stops game time while it is open.
+fn update_pause_menu(
+    mut commands: Commands,
+    paused: Res<Paused>,
+    handle: Res<GlobalTextureAtlas>,
+    mut time: ResMut<Time<Virtual>>,
+    mut focus: ResMut<MenuFocus>,
+    menu_query: Query<Entity, With<PauseMenuItem>>,
+) {
+    if !paused.is_changed() {
+        return;
+    }
+
+    focus.0 = None;
+    for e in menu_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+
+    if !paused.0 {
+        time.unpause();
+        return;
+    }
+
+    time.pause();
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                },
+                background_color: BackgroundColor::from(Color::BLACK.with_a(0.6)),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            PauseMenuItem,
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Paused",
+                TextStyle {
+                    font_size: 64.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            spawn_menu_button(parent, &handle, "Resume", MenuButton::Resume);
+            spawn_menu_button(parent, &handle, "Main Menu", MenuButton::QuitToMenu);
+        });
+}
+
+fn reset_pause(mut paused: ResMut<Paused>, mut time: ResMut<Time<Virtual>>) {
+    paused.0 = false;
+    time.unpause();
+}
+
+fn update_settings_labels(
+    settings: Res<Settings>,
+    button_query: Query<(&MenuButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (button, children) in button_query.iter() {
+        let Some(label) = settings_label(*button, &settings) else {
+            continue;
+        };
+
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = label.clone();
             }
-            Interaction::None => {
-                atlas.index = 0;
+        }
+    }
+}
+
+fn update_binding_labels(
+    keymap: Res<Keymap>,
+    pending_rebind: Res<PendingRebind>,
+    button_query: Query<(&MenuButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !keymap.is_changed() && !pending_rebind.is_changed() {
+        return;
+    }
+
+    for (button, children) in button_query.iter() {
+        let MenuButton::Rebind(action, slot) = *button else {
+            continue;
+        };
+
+        let label = if pending_rebind.action == Some(action) && pending_rebind.slot == slot {
+            "Press a key...".to_string()
+        } else {
+            keymap.slot_label(action, slot)
+        };
+
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = label.clone();
             }
         }
     }
 }
 
+fn exit_submenu_on_escape(
+    actions: Res<ActionState>,
+    pending_rebind: Res<PendingRebind>,
+    mut ew: EventWriter<MenuButtonEvent>,
+) {
+    if pending_rebind.action.is_none()
+        && !pending_rebind.is_changed()
+        && actions.just_pressed(InputAction::Quit)
+    {
+        ew.send(MenuButtonEvent(MenuButton::Back));
+    }
+}
+
 fn despawn_main_menu(mut commands: Commands, menu_items_query: Query<Entity, With<MainMenuItem>>) {
     for e in menu_items_query.iter() {
         commands.entity(e).despawn_recursive();
     }
 }
+
+fn despawn_controls_menu(
+    mut commands: Commands,
+    menu_items_query: Query<Entity, With<ControlsMenuItem>>,
+) {
+    for e in menu_items_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn despawn_settings_menu(
+    mut commands: Commands,
+    menu_items_query: Query<Entity, With<SettingsMenuItem>>,
+) {
+    for e in menu_items_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
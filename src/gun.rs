@@ -1,10 +1,16 @@
 use crate::*;
-use crate::{player::Player, state::GameState, CursorPosition};
+use crate::audio::{Sfx, SfxEvent};
+use crate::damage::{Damage, DamageKind};
+use crate::input::{ActionState, InputAction};
+use crate::player::GoldCount;
+use crate::status::{OnHitStatus, StatusKind};
+use crate::{player::Player, state::playing, CursorPosition};
 use bevy::{
     math::{vec2, vec3},
     prelude::*,
     time::Stopwatch,
 };
+use rand::Rng;
 use std::f32::consts::PI;
 use std::time::Instant;
 
@@ -15,11 +21,88 @@ pub struct Bullet;
 #[derive(Component)]
 pub struct GunTimer(pub Stopwatch);
 
+/// Status the gun's bullets inflict, cycled with `InputAction::SwitchAmmo`.
+#[derive(Component, Default)]
+pub struct GunAmmo(pub Option<StatusKind>);
+
+/// Damage range and crit stats of a weapon, raised by spending gold.
+#[derive(Component, Debug, Clone)]
+pub struct WeaponStats {
+    pub min_damage: f32,
+    pub max_damage: f32,
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
+    pub damage_level: u32,
+    pub crit_level: u32,
+}
+
 #[derive(Component)]
-pub struct SpawnInstant(Instant);
+pub struct SpawnInstant(pub Instant);
 
 #[derive(Component)]
-pub struct BulletDirection(Vec3);
+pub struct BulletDirection(pub Vec3);
+
+/// Who fired a bullet; bullets only hurt the other side.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Faction {
+    Player,
+    Enemy,
+}
+
+impl Faction {
+    pub fn bullet_speed(&self) -> f32 {
+        match self {
+            Faction::Player => BULLET_SPEED,
+            Faction::Enemy => ENEMY_BULLET_SPEED,
+        }
+    }
+}
+
+impl Default for WeaponStats {
+    fn default() -> Self {
+        Self {
+            min_damage: GUN_MIN_DAMAGE,
+            max_damage: GUN_MAX_DAMAGE,
+            crit_chance: GUN_CRIT_CHANCE,
+            crit_multiplier: GUN_CRIT_MULTIPLIER,
+            damage_level: 0,
+            crit_level: 0,
+        }
+    }
+}
+
+impl WeaponStats {
+    pub fn roll(&self, kind: DamageKind) -> Damage {
+        let mut rng = rand::thread_rng();
+        let mut damage = Damage::new(rng.gen_range(self.min_damage..=self.max_damage), kind);
+        if rng.gen_bool(self.crit_chance.clamp(0.0, 1.0) as f64) {
+            damage.amount *= self.crit_multiplier;
+            damage.crit = true;
+        }
+
+        damage
+    }
+
+    pub fn damage_upgrade_cost(&self) -> f32 {
+        WEAPON_UPGRADE_BASE_COST * (self.damage_level + 1) as f32
+    }
+
+    pub fn crit_upgrade_cost(&self) -> f32 {
+        WEAPON_UPGRADE_BASE_COST * (self.crit_level + 1) as f32
+    }
+
+    fn upgrade_damage(&mut self) {
+        self.min_damage += WEAPON_DAMAGE_UPGRADE;
+        self.max_damage += WEAPON_DAMAGE_UPGRADE;
+        self.damage_level += 1;
+    }
+
+    fn upgrade_crit(&mut self) {
+        self.crit_chance = (self.crit_chance + CRIT_CHANCE_UPGRADE).min(MAX_CRIT_CHANCE);
+        self.crit_multiplier += CRIT_MULTIPLIER_UPGRADE;
+        self.crit_level += 1;
+    }
+}
 
 pub struct GunPlugin;
 
@@ -30,9 +113,11 @@ impl Plugin for GunPlugin {
             (
                   update_gun_transform,
                   handle_gun_input,
+                  switch_ammo,
+                  handle_weapon_upgrades,
                   update_bullets,
                   despawn_old_bullets,
-            ).run_if(in_state(GameState::InGame)),
+            ).run_if(playing),
         );
     }
 }
@@ -77,24 +162,27 @@ fn update_gun_transform(
     gun_transform.translation.z = 15.0;
 }
 
-fn update_bullets(mut bullet_query: Query<(&mut Transform, &BulletDirection), With<Bullet>>) {
+fn update_bullets(
+    mut bullet_query: Query<(&mut Transform, &BulletDirection, &Faction), With<Bullet>>,
+) {
     if bullet_query.is_empty() {
         return;
     }
 
-    for (mut t, dir) in bullet_query.iter_mut() {
-        t.translation += dir.0.normalize() * Vec3::splat(BULLET_SPEED);
+    for (mut t, dir, faction) in bullet_query.iter_mut() {
+        t.translation += dir.0.normalize() * Vec3::splat(faction.bullet_speed());
         t.translation.z = 10.0;
     }
 }
 
 fn handle_gun_input(
     mut commands: Commands,
-    mut gun_query: Query<(&Transform, &mut GunTimer), With<Gun>>,
+    mut gun_query: Query<(&Transform, &mut GunTimer, &GunAmmo, &WeaponStats), With<Gun>>,
     time: Res<Time>,
     handle: Res<GlobalTextureAtlas>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    actions: Res<ActionState>,
     mut player_query: Query<&mut Player, With<Player>>,
+    mut sfx_events: EventWriter<SfxEvent>,
 ) {
     if player_query.is_empty() {
         return;
@@ -102,16 +190,16 @@ fn handle_gun_input(
     
     let mut player = player_query.single_mut();
 
-    if mouse_button_input.pressed(MouseButton::Left) {
+    if actions.pressed(InputAction::Fire) {
         player.attacks = true;
     }
 
-    if !mouse_button_input.pressed(MouseButton::Left) || gun_query.is_empty() {
+    if !actions.pressed(InputAction::Fire) || gun_query.is_empty() {
         player.attacks = false;
         return;
     }
 
-    let (gun_transform, mut gun_timer) = gun_query.single_mut();
+    let (gun_transform, mut gun_timer, ammo, stats) = gun_query.single_mut();
     let gun_pos = gun_transform.translation.truncate();
     gun_timer.0.tick(time.delta());
 
@@ -119,21 +207,78 @@ fn handle_gun_input(
 
     if gun_timer.0.elapsed_secs() >= BULLET_SPAWN_INTERVAL {
         gun_timer.0.reset();
+        sfx_events.send(SfxEvent::new(Sfx::Shoot));
 
-        commands.spawn((
+        let mut bullet = commands.spawn((
             SpriteSheetBundle {
                 texture: handle.image.clone().unwrap(),
                 atlas: TextureAtlas {
                     layout: handle.layout.clone().unwrap(),
                     index: 16,
                   },
+                sprite: Sprite {
+                    color: ammo.0.map_or(Color::WHITE, |kind| kind.tint()),
+                    ..default()
+                },
                 transform: Transform::from_translation(vec3(gun_pos.x, gun_pos.y, 1.0))
                     .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
                 ..default()
             },
             Bullet,
+            Faction::Player,
+            stats.roll(ammo.0.map_or(DamageKind::Physical, DamageKind::from)),
             BulletDirection(*bullet_direction),
             SpawnInstant(Instant::now()),
         ));
+        if let Some(kind) = ammo.0 {
+            bullet.insert(OnHitStatus(kind));
+        }
+    }
+}
+
+fn switch_ammo(actions: Res<ActionState>, mut gun_query: Query<&mut GunAmmo, With<Gun>>) {
+    if !actions.just_pressed(InputAction::SwitchAmmo) {
+        return;
+    }
+
+    for mut ammo in gun_query.iter_mut() {
+        ammo.0 = match ammo.0 {
+            None => Some(StatusKind::ALL[0]),
+            Some(kind) => StatusKind::ALL
+                .iter()
+                .position(|other| *other == kind)
+                .and_then(|index| StatusKind::ALL.get(index + 1))
+                .copied(),
+        };
+    }
+}
+
+fn handle_weapon_upgrades(
+    actions: Res<ActionState>,
+    mut player_query: Query<&mut GoldCount, With<Player>>,
+    mut gun_query: Query<&mut WeaponStats, With<Gun>>,
+    mut sfx_events: EventWriter<SfxEvent>,
+) {
+    let (Ok(mut gold), Ok(mut stats)) = (player_query.get_single_mut(), gun_query.get_single_mut())
+    else {
+        return;
+    };
+
+    if actions.just_pressed(InputAction::UpgradeDamage) {
+        let cost = stats.damage_upgrade_cost();
+        if gold.0 >= cost {
+            gold.0 -= cost;
+            stats.upgrade_damage();
+            sfx_events.send(SfxEvent::new(Sfx::GoldPickup));
+        }
+    }
+
+    if actions.just_pressed(InputAction::UpgradeCrit) {
+        let cost = stats.crit_upgrade_cost();
+        if gold.0 >= cost {
+            gold.0 -= cost;
+            stats.upgrade_crit();
+            sfx_events.send(SfxEvent::new(Sfx::GoldPickup));
+        }
     }
 }
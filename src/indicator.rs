@@ -0,0 +1,189 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use bevy::utils::Duration;
+
+use crate::castle::{Castle, CastleUnderAttack};
+use crate::collision::EnemyKdTree;
+use crate::state::{playing, GameState};
+use crate::world::GameEntity;
+use crate::*;
+
+pub struct ThreatIndicatorPlugin;
+
+#[derive(Component)]
+struct ThreatIndicator;
+
+struct Threat {
+    screen_pos: Vec2,
+    distance: f32,
+    count: usize,
+}
+
+impl Plugin for ThreatIndicatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::InGame), spawn_threat_indicators)
+            .add_systems(
+                Update,
+                update_threat_indicators
+                    .run_if(on_timer(Duration::from_secs_f32(KD_TREE_REFRESH_RATE)))
+                    .run_if(playing),
+            );
+    }
+}
+
+/// A fixed pool: one indicator per sector plus one for the castle, hidden until needed.
+fn spawn_threat_indicators(mut commands: Commands, handle: Res<GlobalTextureAtlas>) {
+    for _ in 0..=THREAT_INDICATOR_SECTORS {
+        commands.spawn((
+            AtlasImageBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    display: Display::None,
+                    ..default()
+                },
+                texture_atlas: TextureAtlas {
+                    layout: handle.layout.clone().unwrap(),
+                    index: THREAT_INDICATOR_SPRITE_INDEX,
+                },
+                image: UiImage::new(handle.image.clone().unwrap()),
+                ..default()
+            },
+            ThreatIndicator,
+            GameEntity,
+        ));
+    }
+}
+
+fn update_threat_indicators(
+    mut indicator_query: Query<
+        (&mut Style, &mut BackgroundColor, &mut Transform),
+        With<ThreatIndicator>,
+    >,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    castle_query: Query<&Transform, (With<Castle>, Without<ThreatIndicator>)>,
+    enemy_tree: Res<EnemyKdTree>,
+    under_attack: Res<CastleUnderAttack>,
+    ui_scale: Res<UiScale>,
+) {
+    for (mut style, _, _) in indicator_query.iter_mut() {
+        style.display = Display::None;
+    }
+
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    let Ok(castle_transform) = castle_query.get_single() else {
+        return;
+    };
+    let Some(viewport) = camera.logical_viewport_size() else {
+        return;
+    };
+
+    let camera_pos = camera_transform.translation().truncate();
+    let castle_pos = castle_transform.translation.truncate();
+    let to_screen = |pos: Vec2| {
+        camera
+            .world_to_viewport(camera_transform, pos.extend(0.))
+            .filter(|screen_pos| is_off_screen(*screen_pos, viewport))
+    };
+
+    let mut sectors: Vec<Option<Threat>> = (0..THREAT_INDICATOR_SECTORS).map(|_| None).collect();
+    for enemy in enemy_tree
+        .0
+        .within_radius(&[castle_pos.x, castle_pos.y], THREAT_CASTLE_RADIUS)
+    {
+        let Some(screen_pos) = to_screen(enemy.pos) else {
+            continue;
+        };
+
+        let distance = enemy.pos.distance(camera_pos);
+        let sector = &mut sectors[sector_index(screen_pos - viewport / 2.0)];
+        match sector {
+            Some(threat) => {
+                threat.count += 1;
+                if distance < threat.distance {
+                    threat.screen_pos = screen_pos;
+                    threat.distance = distance;
+                }
+            }
+            None => {
+                *sector = Some(Threat {
+                    screen_pos,
+                    distance,
+                    count: 1,
+                })
+            }
+        }
+    }
+
+    let mut indicators: Vec<(Threat, Color, f32)> = sectors
+        .into_iter()
+        .flatten()
+        .map(|threat| (threat, Color::RED, 1.0))
+        .collect();
+
+    if !under_attack.0.finished() {
+        if let Some(screen_pos) = to_screen(castle_pos) {
+            let threat = Threat {
+                screen_pos,
+                distance: castle_pos.distance(camera_pos),
+                count: 1,
+            };
+            indicators.push((threat, Color::CYAN, 1.5));
+        }
+    }
+
+    for ((threat, color, base_scale), (mut style, mut background, mut transform)) in
+        indicators.into_iter().zip(indicator_query.iter_mut())
+    {
+        let count_scale =
+            (1.0 + (threat.count - 1) as f32 * 0.25).min(THREAT_INDICATOR_MAX_COUNT_SCALE);
+        let distance_scale = 1.0 - (threat.distance / THREAT_INDICATOR_FAR_DISTANCE).min(1.0) * 0.5;
+        let size = THREAT_INDICATOR_SIZE * base_scale * count_scale * distance_scale;
+
+        let center = viewport / 2.0;
+        let dir = threat.screen_pos - center;
+        let edge = center + dir * edge_distance(dir, center - THREAT_INDICATOR_MARGIN);
+        let edge = edge / ui_scale.0;
+
+        style.display = Display::Flex;
+        style.left = Val::Px(edge.x - size);
+        style.top = Val::Px(edge.y - size / 2.0);
+        style.width = Val::Px(size * 2.0);
+        style.height = Val::Px(size);
+        background.0 = color;
+        transform.rotation = Quat::from_rotation_z(dir.y.atan2(dir.x));
+    }
+}
+
+fn is_off_screen(screen_pos: Vec2, viewport: Vec2) -> bool {
+    screen_pos.x < 0.0
+        || screen_pos.y < 0.0
+        || screen_pos.x > viewport.x
+        || screen_pos.y > viewport.y
+}
+
+fn sector_index(dir: Vec2) -> usize {
+    let angle = dir.y.atan2(dir.x) + PI;
+    let sector = (angle / (2.0 * PI) * THREAT_INDICATOR_SECTORS as f32) as usize;
+
+    sector.min(THREAT_INDICATOR_SECTORS - 1)
+}
+
+/// Fraction of `dir` at which it leaves a box of `half_size` around the screen center.
+fn edge_distance(dir: Vec2, half_size: Vec2) -> f32 {
+    let tx = if dir.x != 0.0 {
+        half_size.x / dir.x.abs()
+    } else {
+        f32::MAX
+    };
+    let ty = if dir.y != 0.0 {
+        half_size.y / dir.y.abs()
+    } else {
+        f32::MAX
+    };
+
+    tx.min(ty).max(0.0)
+}
@@ -0,0 +1,481 @@
+use std::collections::{BTreeMap, HashSet};
+
+use bevy::app::AppExit;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{load_config, save_config};
+use crate::gamepad::ActiveGamepad;
+use crate::state::GameState;
+use crate::*;
+
+pub struct InputActionPlugin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum InputAction {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Fire,
+    Dash,
+    FireRocket,
+    SwitchAmmo,
+    UpgradeDamage,
+    UpgradeCrit,
+    FollowCamera,
+    PanCamera,
+    Pause,
+    MenuUp,
+    MenuDown,
+    MenuSelect,
+    Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType),
+}
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct Keymap(pub BTreeMap<InputAction, Vec<InputBinding>>);
+
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashSet<InputAction>,
+    just_pressed: HashSet<InputAction>,
+}
+
+#[derive(Resource, Default)]
+pub struct PendingRebind {
+    pub action: Option<InputAction>,
+    pub slot: usize,
+    armed: bool,
+}
+
+impl Plugin for InputActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_keymap())
+            .insert_resource(ActionState::default())
+            .insert_resource(PendingRebind::default())
+            .add_systems(PreUpdate, update_action_state.after(InputSystem))
+            .add_systems(
+                Update,
+                (
+                    capture_rebind.run_if(in_state(GameState::Controls)),
+                    handle_quit.run_if(in_state(GameState::MainMenu)),
+                ),
+            );
+    }
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 17] = [
+        InputAction::MoveUp,
+        InputAction::MoveDown,
+        InputAction::MoveLeft,
+        InputAction::MoveRight,
+        InputAction::Fire,
+        InputAction::Dash,
+        InputAction::FireRocket,
+        InputAction::SwitchAmmo,
+        InputAction::UpgradeDamage,
+        InputAction::UpgradeCrit,
+        InputAction::FollowCamera,
+        InputAction::PanCamera,
+        InputAction::Pause,
+        InputAction::MenuUp,
+        InputAction::MenuDown,
+        InputAction::MenuSelect,
+        InputAction::Quit,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            InputAction::MoveUp => "Move Up",
+            InputAction::MoveDown => "Move Down",
+            InputAction::MoveLeft => "Move Left",
+            InputAction::MoveRight => "Move Right",
+            InputAction::Fire => "Fire",
+            InputAction::Dash => "Dash",
+            InputAction::FireRocket => "Fire Rocket",
+            InputAction::SwitchAmmo => "Switch Ammo",
+            InputAction::UpgradeDamage => "Upgrade Damage",
+            InputAction::UpgradeCrit => "Upgrade Crit",
+            InputAction::FollowCamera => "Follow Camera",
+            InputAction::PanCamera => "Pan Camera",
+            InputAction::Pause => "Pause",
+            InputAction::MenuUp => "Menu Up",
+            InputAction::MenuDown => "Menu Down",
+            InputAction::MenuSelect => "Menu Select",
+            InputAction::Quit => "Back / Quit",
+        }
+    }
+
+    /// Menu actions are only read in menus, so they may share bindings with gameplay ones.
+    pub fn is_menu(&self) -> bool {
+        matches!(
+            self,
+            InputAction::MenuUp | InputAction::MenuDown | InputAction::MenuSelect | InputAction::Quit
+        )
+    }
+
+    fn conflicts_with(&self, other: InputAction) -> bool {
+        *self != other && self.is_menu() == other.is_menu()
+    }
+}
+
+impl InputBinding {
+    pub fn label(&self) -> String {
+        match self {
+            InputBinding::Key(key) => format!("{key:?}").trim_start_matches("Key").to_string(),
+            InputBinding::Mouse(button) => format!("Mouse {button:?}"),
+            InputBinding::Gamepad(button) => format!("Pad {button:?}"),
+        }
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use InputBinding::*;
+
+        Self(BTreeMap::from([
+            (InputAction::MoveUp, vec![Key(KeyCode::KeyW), Key(KeyCode::ArrowUp)]),
+            (InputAction::MoveDown, vec![Key(KeyCode::KeyS), Key(KeyCode::ArrowDown)]),
+            (InputAction::MoveLeft, vec![Key(KeyCode::KeyA), Key(KeyCode::ArrowLeft)]),
+            (InputAction::MoveRight, vec![Key(KeyCode::KeyD), Key(KeyCode::ArrowRight)]),
+            (
+                InputAction::Fire,
+                vec![Mouse(MouseButton::Left), Gamepad(GamepadButtonType::RightTrigger2)],
+            ),
+            (
+                InputAction::Dash,
+                vec![Key(KeyCode::ShiftLeft), Gamepad(GamepadButtonType::South)],
+            ),
+            (
+                InputAction::FireRocket,
+                vec![Key(KeyCode::KeyE), Gamepad(GamepadButtonType::RightTrigger)],
+            ),
+            (
+                InputAction::SwitchAmmo,
+                vec![Key(KeyCode::KeyQ), Gamepad(GamepadButtonType::West)],
+            ),
+            (
+                InputAction::UpgradeDamage,
+                vec![Key(KeyCode::Digit1), Gamepad(GamepadButtonType::DPadUp)],
+            ),
+            (
+                InputAction::UpgradeCrit,
+                vec![Key(KeyCode::Digit2), Gamepad(GamepadButtonType::DPadRight)],
+            ),
+            (
+                InputAction::FollowCamera,
+                vec![Key(KeyCode::Space), Gamepad(GamepadButtonType::LeftTrigger2)],
+            ),
+            (InputAction::PanCamera, vec![Mouse(MouseButton::Right)]),
+            (
+                InputAction::Pause,
+                vec![Key(KeyCode::Escape), Gamepad(GamepadButtonType::Start)],
+            ),
+            (
+                InputAction::MenuUp,
+                vec![Key(KeyCode::ArrowUp), Gamepad(GamepadButtonType::DPadUp)],
+            ),
+            (
+                InputAction::MenuDown,
+                vec![Key(KeyCode::ArrowDown), Gamepad(GamepadButtonType::DPadDown)],
+            ),
+            (
+                InputAction::MenuSelect,
+                vec![Key(KeyCode::Enter), Gamepad(GamepadButtonType::South)],
+            ),
+            (
+                InputAction::Quit,
+                vec![Key(KeyCode::Escape), Gamepad(GamepadButtonType::Select)],
+            ),
+        ]))
+    }
+}
+
+impl Keymap {
+    pub fn bindings(&self, action: InputAction) -> &[InputBinding] {
+        self.0.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn mouse_buttons(&self, action: InputAction) -> Vec<MouseButton> {
+        self.bindings(action)
+            .iter()
+            .filter_map(|binding| match binding {
+                InputBinding::Mouse(button) => Some(*button),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn label(&self, action: InputAction) -> String {
+        let labels: Vec<String> = self.bindings(action).iter().map(InputBinding::label).collect();
+        if labels.is_empty() {
+            return "-".to_string();
+        }
+
+        labels.join(" / ")
+    }
+
+    pub fn slot_label(&self, action: InputAction, slot: usize) -> String {
+        self.bindings(action)
+            .get(slot)
+            .map_or("-".to_string(), InputBinding::label)
+    }
+
+    /// Puts `binding` into `slot` of `action`, leaving its other slots alone. An action that
+    /// already used `binding` in the same context gets the replaced binding instead, so one
+    /// input never triggers two actions. Slots past the first empty one are ignored.
+    pub fn rebind(&mut self, action: InputAction, slot: usize, binding: InputBinding) {
+        if slot >= BINDING_SLOTS || slot > self.bindings(action).len() {
+            return;
+        }
+        let previous = self.bindings(action).get(slot).copied();
+
+        for (other, bindings) in self.0.iter_mut() {
+            if !action.conflicts_with(*other) {
+                continue;
+            }
+            if let Some(index) = bindings.iter().position(|b| *b == binding) {
+                match previous.filter(|previous| !bindings.contains(previous)) {
+                    Some(previous) => bindings[index] = previous,
+                    None => {
+                        bindings.remove(index);
+                    }
+                }
+            }
+        }
+
+        let bindings = self.0.entry(action).or_default();
+        if let Some(existing) = bindings.iter().position(|b| *b == binding) {
+            if slot < bindings.len() {
+                bindings.swap(existing, slot);
+            }
+        } else if slot < bindings.len() {
+            bindings[slot] = binding;
+        } else {
+            bindings.push(binding);
+        }
+    }
+
+    /// Gives actions added since the keymap was saved their defaults, minus any already taken.
+    fn with_missing_defaults(mut self) -> Self {
+        for (action, bindings) in Keymap::default().0 {
+            if self.0.contains_key(&action) {
+                continue;
+            }
+
+            let free = bindings
+                .into_iter()
+                .filter(|binding| {
+                    !self.0.iter().any(|(other, used)| {
+                        action.conflicts_with(*other) && used.contains(binding)
+                    })
+                })
+                .collect();
+            self.0.insert(action, free);
+        }
+
+        self
+    }
+}
+
+impl PendingRebind {
+    pub fn start(&mut self, action: InputAction, slot: usize) {
+        self.action = Some(action);
+        self.slot = slot;
+        self.armed = false;
+    }
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+}
+
+pub fn load_keymap() -> Keymap {
+    load_config::<Keymap>(KEYMAP_FILE_NAME)
+        .unwrap_or_default()
+        .with_missing_defaults()
+}
+
+pub fn save_keymap(keymap: &Keymap) {
+    save_config(KEYMAP_FILE_NAME, keymap);
+}
+
+fn update_action_state(
+    mut state: ResMut<ActionState>,
+    keymap: Res<Keymap>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    active_gamepad: Res<ActiveGamepad>,
+) {
+    state.pressed.clear();
+    state.just_pressed.clear();
+
+    for (action, bindings) in keymap.0.iter() {
+        for binding in bindings {
+            let (pressed, just_pressed) = match binding {
+                InputBinding::Key(key) => {
+                    (keyboard_input.pressed(*key), keyboard_input.just_pressed(*key))
+                }
+                InputBinding::Mouse(button) => (
+                    mouse_button_input.pressed(*button),
+                    mouse_button_input.just_pressed(*button),
+                ),
+                InputBinding::Gamepad(button) => (
+                    active_gamepad.pressed(&gamepad_buttons, *button),
+                    active_gamepad.just_pressed(&gamepad_buttons, *button),
+                ),
+            };
+
+            if pressed {
+                state.pressed.insert(*action);
+            }
+            if just_pressed {
+                state.just_pressed.insert(*action);
+            }
+        }
+    }
+}
+
+fn capture_rebind(
+    mut pending: ResMut<PendingRebind>,
+    mut keymap: ResMut<Keymap>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+) {
+    let Some(action) = pending.action else {
+        return;
+    };
+
+    // Wait until the click or button press that started the rebind is released.
+    if !pending.armed {
+        pending.armed = keyboard_input.get_pressed().next().is_none()
+            && mouse_button_input.get_pressed().next().is_none()
+            && gamepad_buttons.get_pressed().next().is_none();
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        *pending = PendingRebind::default();
+        return;
+    }
+
+    let binding = keyboard_input
+        .get_just_pressed()
+        .next()
+        .map(|key| InputBinding::Key(*key))
+        .or_else(|| {
+            mouse_button_input
+                .get_just_pressed()
+                .next()
+                .map(|button| InputBinding::Mouse(*button))
+        })
+        .or_else(|| {
+            gamepad_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| InputBinding::Gamepad(button.button_type))
+        });
+
+    if let Some(binding) = binding {
+        keymap.rebind(action, pending.slot, binding);
+        *pending = PendingRebind::default();
+    }
+}
+
+fn handle_quit(actions: Res<ActionState>, mut ew: EventWriter<AppExit>) {
+    if actions.just_pressed(InputAction::Quit) {
+        ew.send(AppExit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use InputBinding::Key;
+
+    #[test]
+    fn rebind_replaces_only_the_edited_slot() {
+        let mut keymap = Keymap::default();
+        keymap.rebind(InputAction::MoveUp, 1, Key(KeyCode::KeyI));
+        assert_eq!(
+            keymap.bindings(InputAction::MoveUp),
+            [Key(KeyCode::KeyW), Key(KeyCode::KeyI)]
+        );
+    }
+
+    #[test]
+    fn rebind_ignores_slots_past_the_first_empty_one() {
+        let mut keymap = Keymap::default();
+        keymap.0.insert(InputAction::PanCamera, vec![]);
+        keymap.rebind(InputAction::PanCamera, 1, Key(KeyCode::KeyP));
+        assert!(keymap.bindings(InputAction::PanCamera).is_empty());
+
+        keymap.rebind(InputAction::PanCamera, BINDING_SLOTS, Key(KeyCode::KeyP));
+        assert!(keymap.bindings(InputAction::PanCamera).is_empty());
+
+        keymap.rebind(InputAction::PanCamera, 0, Key(KeyCode::KeyP));
+        assert_eq!(
+            keymap.bindings(InputAction::PanCamera),
+            [Key(KeyCode::KeyP)]
+        );
+    }
+
+    #[test]
+    fn rebind_swaps_a_conflicting_binding() {
+        let mut keymap = Keymap::default();
+        keymap.rebind(InputAction::MoveUp, 0, Key(KeyCode::KeyS));
+        assert_eq!(keymap.bindings(InputAction::MoveUp)[0], Key(KeyCode::KeyS));
+        assert_eq!(
+            keymap.bindings(InputAction::MoveDown)[0],
+            Key(KeyCode::KeyW)
+        );
+    }
+
+    #[test]
+    fn rebind_allows_sharing_across_menu_and_game() {
+        let mut keymap = Keymap::default();
+        keymap.rebind(InputAction::MenuSelect, 0, Key(KeyCode::Space));
+        assert_eq!(
+            keymap.bindings(InputAction::MenuSelect)[0],
+            Key(KeyCode::Space)
+        );
+        assert_eq!(
+            keymap.bindings(InputAction::FollowCamera)[0],
+            Key(KeyCode::Space)
+        );
+    }
+
+    #[test]
+    fn missing_actions_get_their_free_defaults() {
+        let mut keymap = Keymap::default();
+        keymap.0.remove(&InputAction::Pause);
+        keymap
+            .0
+            .insert(InputAction::Dash, vec![Key(KeyCode::Escape)]);
+
+        let keymap = keymap.with_missing_defaults();
+        assert_eq!(
+            keymap.bindings(InputAction::Pause),
+            [InputBinding::Gamepad(GamepadButtonType::Start)]
+        );
+        assert_eq!(keymap.bindings(InputAction::Dash), [Key(KeyCode::Escape)]);
+    }
+}
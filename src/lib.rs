@@ -13,6 +13,26 @@ pub mod gold;
 pub mod pan_cam;
 pub mod cursor;
 pub mod castle;
+pub mod gamepad;
+pub mod config;
+pub mod input;
+pub mod settings;
+pub mod audio;
+pub mod minimap;
+pub mod indicator;
+pub mod chunk;
+pub mod obstacle;
+pub mod navigation;
+pub mod ai;
+pub mod boss;
+pub mod elite;
+pub mod portal;
+pub mod difficulty;
+pub mod director;
+pub mod status;
+pub mod damage;
+pub mod feedback;
+pub mod explosion;
 
 pub use constants::*;
-pub use resourses::*;
\ No newline at end of file
+pub use resourses::*;
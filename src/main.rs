@@ -1,5 +1,4 @@
 use bevy::prelude::*;
-use bevy::window::close_on_esc;
 
 use hell_game::castle::CastlePlugin;
 use hell_game::cursor::CursorPlugin;
@@ -7,15 +6,35 @@ use hell_game::gold::GoldPlugin;
 use hell_game::gui::GuiPlugin;
 use hell_game::collision::CollisionPlugin;
 use hell_game::enemy::EnemyPlagin;
+use hell_game::gamepad::GamepadInputPlugin;
+use hell_game::input::InputActionPlugin;
 use hell_game::animation::AnimationPlugin;
+use hell_game::audio::GameAudioPlugin;
+use hell_game::minimap::MinimapPlugin;
+use hell_game::indicator::ThreatIndicatorPlugin;
+use hell_game::chunk::ChunkPlugin;
+use hell_game::navigation::NavigationPlugin;
+use hell_game::ai::AiPlugin;
+use hell_game::boss::BossPlugin;
+use hell_game::elite::ElitePlugin;
+use hell_game::portal::PortalPlugin;
+use hell_game::difficulty::DifficultyPlugin;
+use hell_game::director::DirectorPlugin;
+use hell_game::status::StatusPlugin;
+use hell_game::damage::DamagePlugin;
+use hell_game::feedback::FeedbackPlugin;
+use hell_game::explosion::ExplosionPlugin;
 use hell_game::camera::FollowCameraPlugin;
 use hell_game::gun::GunPlugin;
 use hell_game::player::PlayerPlugin;
+use hell_game::settings::{load_settings, SettingsPlugin};
 use hell_game::state::GameState;
 use hell_game::world::WorldPlugin;
 use hell_game::*;
 
 fn main() {
+    let settings = load_settings();
+
     App::new()
         .init_state::<GameState>()
         .add_plugins(
@@ -23,10 +42,11 @@ fn main() {
                 .set(ImagePlugin::default_nearest())
                 .set(WindowPlugin {
                     primary_window: Some(Window {
-                        // mode: bevy::window::WindowMode::Fullscreen,
-                        resizable: false,
+                        mode: settings.window_mode(),
+                        present_mode: settings.present_mode(),
+                        resizable: true,
                         focused: true,
-                        resolution: (WW, WH).into(),
+                        resolution: settings.resolution.into(),
                         ..default()
                     }),
                     ..default()
@@ -35,19 +55,37 @@ fn main() {
         .insert_resource(ClearColor(Color::rgb_u8(
             BG_COLOR.0, BG_COLOR.1, BG_COLOR.2,
         )))
+        .insert_resource(settings)
+        .add_plugins(SettingsPlugin)
         .add_plugins(FollowCameraPlugin)
+        .add_plugins(GamepadInputPlugin)
+        .add_plugins(InputActionPlugin)
         .add_plugins(GunPlugin)
         .add_plugins(PlayerPlugin)
         .add_plugins(CastlePlugin)
         .add_plugins(ResourcesPlugin)
         .add_plugins(WorldPlugin)
+        .add_plugins(ChunkPlugin)
+        .add_plugins(NavigationPlugin)
+        .add_plugins(AiPlugin)
+        .add_plugins(BossPlugin)
+        .add_plugins(ElitePlugin)
+        .add_plugins(PortalPlugin)
+        .add_plugins(DifficultyPlugin)
+        .add_plugins(DirectorPlugin)
+        .add_plugins(StatusPlugin)
+        .add_plugins(DamagePlugin)
+        .add_plugins(FeedbackPlugin)
+        .add_plugins(ExplosionPlugin)
         .add_plugins(EnemyPlagin)
         .add_plugins(CursorPlugin)
         .add_plugins(GuiPlugin)
         .add_plugins(AnimationPlugin)
         .add_plugins(CollisionPlugin)
         .add_plugins(GoldPlugin)
+        .add_plugins(GameAudioPlugin)
+        .add_plugins(MinimapPlugin)
+        .add_plugins(ThreatIndicatorPlugin)
         .insert_resource(Msaa::Off)
-        .add_systems(Update, close_on_esc)
         .run();
 }
@@ -0,0 +1,214 @@
+use bevy::math::vec2;
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use bevy::utils::Duration;
+use bevy::window::PrimaryWindow;
+
+use crate::castle::Castle;
+use crate::collision::{EnemyKdTree, GoldKdTree};
+use crate::enemy::EnemyType;
+use crate::player::Player;
+use crate::portal::Portal;
+use crate::state::{playing, GameState};
+use crate::world::GameEntity;
+use crate::*;
+
+pub struct MinimapPlugin;
+
+#[derive(Component)]
+struct Minimap;
+
+#[derive(Component)]
+struct MinimapDot;
+
+#[derive(Component)]
+struct MinimapView;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::InGame), spawn_minimap)
+            .add_systems(
+                Update,
+                (
+                    update_minimap_dots
+                        .run_if(on_timer(Duration::from_secs_f32(KD_TREE_REFRESH_RATE))),
+                    update_minimap_view,
+                    handle_minimap_click,
+                )
+                    .run_if(playing),
+            );
+    }
+}
+
+fn spawn_minimap(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(10.0),
+                    right: Val::Px(10.0),
+                    width: Val::Px(MINIMAP_SIZE),
+                    height: Val::Px(MINIMAP_SIZE),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                background_color: BackgroundColor::from(Color::BLACK.with_a(0.7)),
+                border_color: BorderColor(Color::WHITE.with_a(0.8)),
+                ..default()
+            },
+            Interaction::default(),
+            Minimap,
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..default()
+                    },
+                    border_color: BorderColor(Color::WHITE.with_a(0.5)),
+                    ..default()
+                },
+                MinimapView,
+            ));
+        });
+}
+
+/// Dots are pooled: existing nodes are moved and recoloured, extras hidden, and new ones
+/// spawned only when the pool runs short, so the UI is not rebuilt every refresh.
+#[allow(clippy::too_many_arguments)]
+fn update_minimap_dots(
+    mut commands: Commands,
+    minimap_query: Query<Entity, With<Minimap>>,
+    mut dot_query: Query<(&mut Style, &mut BackgroundColor), With<MinimapDot>>,
+    enemy_tree: Res<EnemyKdTree>,
+    gold_tree: Res<GoldKdTree>,
+    enemy_type_query: Query<&EnemyType>,
+    player_query: Query<&Transform, With<Player>>,
+    castle_query: Query<&Transform, With<Castle>>,
+    portal_query: Query<&Transform, With<Portal>>,
+) {
+    let Ok(minimap) = minimap_query.get_single() else {
+        return;
+    };
+
+    let mut dots = Vec::new();
+    for gold in gold_tree.0.items() {
+        dots.push((gold.pos, Color::GOLD, MINIMAP_DOT_SIZE));
+    }
+    for enemy in enemy_tree.0.items() {
+        if let Ok(enemy_type) = enemy_type_query.get(enemy.entity) {
+            dots.push((enemy.pos, enemy_type.color(), MINIMAP_DOT_SIZE));
+        }
+    }
+    for portal in portal_query.iter() {
+        dots.push((
+            portal.translation.truncate(),
+            PORTAL_WARNING_COLOR,
+            MINIMAP_DOT_SIZE * 2.0,
+        ));
+    }
+    for castle in castle_query.iter() {
+        dots.push((castle.translation.truncate(), Color::CYAN, MINIMAP_DOT_SIZE * 3.0));
+    }
+    for player in player_query.iter() {
+        dots.push((player.translation.truncate(), Color::WHITE, MINIMAP_DOT_SIZE * 2.0));
+    }
+
+    let mut dots = dots.into_iter();
+    for (mut style, mut background) in dot_query.iter_mut() {
+        match dots.next() {
+            Some((pos, color, size)) => {
+                set_dot_style(&mut style, pos, size);
+                background.0 = color;
+            }
+            None => style.display = Display::None,
+        }
+    }
+
+    commands.entity(minimap).with_children(|parent| {
+        for (pos, color, size) in dots {
+            let mut style = Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            };
+            set_dot_style(&mut style, pos, size);
+            parent.spawn((
+                NodeBundle {
+                    style,
+                    background_color: BackgroundColor::from(color),
+                    ..default()
+                },
+                MinimapDot,
+            ));
+        }
+    });
+}
+
+fn set_dot_style(style: &mut Style, pos: Vec2, size: f32) {
+    let (left, top) = world_to_minimap(pos);
+    style.display = Display::Flex;
+    style.left = Val::Percent(left);
+    style.top = Val::Percent(top);
+    style.width = Val::Px(size);
+    style.height = Val::Px(size);
+    style.margin = UiRect::all(Val::Px(-size / 2.0));
+}
+
+fn update_minimap_view(
+    camera_query: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+    mut view_query: Query<&mut Style, With<MinimapView>>,
+) {
+    if camera_query.is_empty() || view_query.is_empty() {
+        return;
+    }
+
+    let (transform, projection) = camera_query.single();
+    let view_min = transform.translation.truncate() + projection.area.min;
+    let view_max = transform.translation.truncate() + projection.area.max;
+    let (left, top) = world_to_minimap(vec2(view_min.x, view_max.y));
+    let (right, bottom) = world_to_minimap(vec2(view_max.x, view_min.y));
+
+    let mut style = view_query.single_mut();
+    style.left = Val::Percent(left);
+    style.top = Val::Percent(top);
+    style.width = Val::Percent(right - left);
+    style.height = Val::Percent(bottom - top);
+}
+
+fn handle_minimap_click(
+    minimap_query: Query<(&Interaction, &Node, &GlobalTransform), With<Minimap>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    ui_scale: Res<UiScale>,
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+) {
+    let Ok((interaction, node, transform)) = minimap_query.get_single() else {
+        return;
+    };
+
+    if *interaction != Interaction::Pressed {
+        return;
+    }
+
+    let Some(cursor) = window_query.get_single().ok().and_then(|w| w.cursor_position()) else {
+        return;
+    };
+
+    let relative = (cursor / ui_scale.0 - transform.translation().truncate()) / node.size();
+    let world_pos = vec2(relative.x * WORLD_W * 2.0, -relative.y * WORLD_H * 2.0);
+
+    for mut camera_transform in camera_query.iter_mut() {
+        camera_transform.translation.x = world_pos.x;
+        camera_transform.translation.y = world_pos.y;
+    }
+}
+
+fn world_to_minimap(pos: Vec2) -> (f32, f32) {
+    let left = (pos.x + WORLD_W) / (WORLD_W * 2.0) * 100.0;
+    let top = (WORLD_H - pos.y) / (WORLD_H * 2.0) * 100.0;
+
+    (left.clamp(0.0, 100.0), top.clamp(0.0, 100.0))
+}
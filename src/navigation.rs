@@ -0,0 +1,347 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use bevy::math::ivec2;
+use bevy::prelude::*;
+
+use crate::castle::Castle;
+use crate::chunk::{chunk_at, chunk_origin};
+use crate::obstacle::Obstacles;
+use crate::state::playing;
+use crate::*;
+
+pub struct NavigationPlugin;
+
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+const NEIGHBOURS: [(IVec2, u32); 8] = [
+    (IVec2::new(1, 0), STRAIGHT_COST),
+    (IVec2::new(-1, 0), STRAIGHT_COST),
+    (IVec2::new(0, 1), STRAIGHT_COST),
+    (IVec2::new(0, -1), STRAIGHT_COST),
+    (IVec2::new(1, 1), DIAGONAL_COST),
+    (IVec2::new(1, -1), DIAGONAL_COST),
+    (IVec2::new(-1, 1), DIAGONAL_COST),
+    (IVec2::new(-1, -1), DIAGONAL_COST),
+];
+
+/// Walkability of the loaded chunks, one cell per `NAV_CELL_SIZE` square.
+#[derive(Resource, Default)]
+pub struct NavGrid {
+    /// Cell in the bottom-left corner of the grid.
+    pub min: IVec2,
+    pub size: IVec2,
+    pub blocked: Vec<bool>,
+    /// Walkable cells bordering the castle footprint, where paths end.
+    pub castle_edge: Vec<IVec2>,
+}
+
+/// Direction to walk from every cell to reach the castle, shared by all enemies.
+#[derive(Resource, Default)]
+pub struct FlowField {
+    pub goal: Option<IVec2>,
+    min: IVec2,
+    size: IVec2,
+    directions: Vec<Vec2>,
+}
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavGrid>()
+            .init_resource::<FlowField>()
+            .add_systems(
+                Update,
+                update_flow_field.run_if(playing),
+            );
+    }
+}
+
+impl NavGrid {
+    /// Covers the bounding box of the loaded chunks; cells in chunks that aren't loaded
+    /// or outside the world are blocked.
+    fn new(obstacles: &Obstacles, castle_pos: Vec2) -> Self {
+        let mut chunks = obstacles.loaded_chunks();
+        let Some(first) = chunks.next() else {
+            return Self::default();
+        };
+        let (min_chunk, max_chunk) = chunks.fold((first, first), |(min, max), chunk| {
+            (min.min(chunk), max.max(chunk))
+        });
+        let min = cell_at(chunk_origin(min_chunk));
+        let size = cell_at(chunk_origin(max_chunk + IVec2::ONE)) - min;
+
+        let mut blocked = Vec::with_capacity((size.x * size.y) as usize);
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let center = cell_center(min + ivec2(x, y));
+                let is_unloaded = !obstacles.is_chunk_loaded(chunk_at(center))
+                    || center.x.abs() > WORLD_W
+                    || center.y.abs() > WORLD_H;
+                let is_castle = center.distance(castle_pos) < CASTLE_FOOTPRINT_RADIUS;
+                let is_rock = obstacles
+                    .near(center, ENEMY_COLLISION_RADIUS)
+                    .any(|obstacle| {
+                        obstacle
+                            .collider
+                            .push_out(obstacle.pos, center, ENEMY_COLLISION_RADIUS)
+                            .is_some()
+                    });
+                blocked.push(is_unloaded || is_rock || is_castle);
+            }
+        }
+
+        let mut grid = Self {
+            min,
+            size,
+            blocked,
+            castle_edge: Vec::new(),
+        };
+        grid.castle_edge = grid.edge_around(castle_pos);
+        grid
+    }
+
+    /// Walkable cells with a neighbour inside the castle footprint.
+    fn edge_around(&self, castle_pos: Vec2) -> Vec<IVec2> {
+        let reach = (CASTLE_FOOTPRINT_RADIUS / NAV_CELL_SIZE).ceil() as i32 + 1;
+        let center = cell_at(castle_pos);
+        let in_footprint =
+            |cell: IVec2| cell_center(cell).distance(castle_pos) < CASTLE_FOOTPRINT_RADIUS;
+
+        let mut edge = Vec::new();
+        for y in -reach..=reach {
+            for x in -reach..=reach {
+                let cell = center + ivec2(x, y);
+                if self.is_walkable(cell)
+                    && NEIGHBOURS
+                        .iter()
+                        .any(|(offset, _)| in_footprint(cell + *offset))
+                {
+                    edge.push(cell);
+                }
+            }
+        }
+
+        edge
+    }
+
+    fn index(&self, cell: IVec2) -> Option<usize> {
+        grid_index(cell - self.min, self.size)
+    }
+
+    pub fn is_walkable(&self, cell: IVec2) -> bool {
+        self.index(cell).is_some_and(|index| !self.blocked[index])
+    }
+}
+
+impl FlowField {
+    /// Dijkstra outwards from the cells around the castle, then point each cell at its
+    /// cheapest neighbour.
+    fn new(grid: &NavGrid, goal: IVec2) -> Self {
+        let mut costs = vec![u32::MAX; grid.blocked.len()];
+        let mut directions = vec![Vec2::ZERO; grid.blocked.len()];
+        let mut queue = BinaryHeap::new();
+
+        for cell in grid.castle_edge.iter() {
+            if let Some(index) = grid.index(*cell) {
+                costs[index] = 0;
+                queue.push(Reverse((0, cell.x, cell.y)));
+            }
+        }
+
+        while let Some(Reverse((cost, x, y))) = queue.pop() {
+            let cell = ivec2(x, y);
+            if cost > costs[grid.index(cell).unwrap()] {
+                continue;
+            }
+
+            for (offset, step) in NEIGHBOURS {
+                let next = cell + offset;
+                if !is_passable(grid, cell, offset) {
+                    continue;
+                }
+
+                let index = grid.index(next).unwrap();
+                let next_cost = cost + step;
+                if next_cost < costs[index] {
+                    costs[index] = next_cost;
+                    queue.push(Reverse((next_cost, next.x, next.y)));
+                }
+            }
+        }
+
+        for y in 0..grid.size.y {
+            for x in 0..grid.size.x {
+                let cell = grid.min + ivec2(x, y);
+                let index = grid.index(cell).unwrap();
+                if costs[index] == u32::MAX || costs[index] == 0 {
+                    continue;
+                }
+
+                let best = NEIGHBOURS
+                    .iter()
+                    .filter(|(offset, _)| is_passable(grid, cell, *offset))
+                    .min_by_key(|(offset, _)| costs[grid.index(cell + *offset).unwrap()]);
+                if let Some((offset, _)) = best {
+                    directions[index] = offset.as_vec2().normalize();
+                }
+            }
+        }
+
+        Self {
+            goal: Some(goal),
+            min: grid.min,
+            size: grid.size,
+            directions,
+        }
+    }
+
+    /// `None` outside the loaded area, on the castle's edge or where the castle is unreachable.
+    pub fn direction(&self, pos: Vec2) -> Option<Vec2> {
+        let index = grid_index(cell_at(pos) - self.min, self.size)?;
+        let dir = self.directions[index];
+        (dir != Vec2::ZERO).then_some(dir)
+    }
+}
+
+fn grid_index(local: IVec2, size: IVec2) -> Option<usize> {
+    if local.x < 0 || local.y < 0 || local.x >= size.x || local.y >= size.y {
+        return None;
+    }
+
+    Some((local.y * size.x + local.x) as usize)
+}
+
+/// Diagonal steps may not cut the corner of a blocked cell.
+fn is_passable(grid: &NavGrid, cell: IVec2, offset: IVec2) -> bool {
+    if !grid.is_walkable(cell + offset) {
+        return false;
+    }
+
+    offset.x == 0
+        || offset.y == 0
+        || (grid.is_walkable(cell + ivec2(offset.x, 0))
+            && grid.is_walkable(cell + ivec2(0, offset.y)))
+}
+
+pub fn cell_at(pos: Vec2) -> IVec2 {
+    (pos / NAV_CELL_SIZE).floor().as_ivec2()
+}
+
+pub fn cell_center(cell: IVec2) -> Vec2 {
+    (cell.as_vec2() + 0.5) * NAV_CELL_SIZE
+}
+
+fn update_flow_field(
+    obstacles: Res<Obstacles>,
+    castle_query: Query<&Transform, With<Castle>>,
+    mut grid: ResMut<NavGrid>,
+    mut flow_field: ResMut<FlowField>,
+) {
+    let Ok(castle_transform) = castle_query.get_single() else {
+        return;
+    };
+
+    let castle_pos = castle_transform.translation.truncate();
+    let goal = cell_at(castle_pos);
+    if !obstacles.is_changed() && flow_field.goal == Some(goal) {
+        return;
+    }
+
+    let new_grid = NavGrid::new(&obstacles, castle_pos);
+    if new_grid.min == grid.min
+        && new_grid.size == grid.size
+        && new_grid.blocked == grid.blocked
+        && flow_field.goal == Some(goal)
+    {
+        return;
+    }
+
+    *flow_field = FlowField::new(&new_grid, goal);
+    *grid = new_grid;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::math::vec2;
+
+    use super::*;
+    use crate::obstacle::{Collider, Obstacle};
+
+    const WALL: Obstacle = Obstacle {
+        pos: Vec2::new(650.0, 50.0),
+        sprite_index: 0,
+        collider: Collider::Box {
+            half_size: Vec2::new(50.0, 300.0),
+        },
+    };
+
+    fn open_area() -> Obstacles {
+        let mut obstacles = Obstacles::default();
+        for y in -1..=1 {
+            for x in -1..=1 {
+                obstacles.insert_chunk(ivec2(x, y), vec![]);
+            }
+        }
+        obstacles
+    }
+
+    #[test]
+    fn grid_covers_only_loaded_walkable_cells() {
+        let grid = NavGrid::new(&open_area(), Vec2::ZERO);
+
+        assert!(!grid.is_walkable(cell_at(Vec2::ZERO)));
+        assert!(grid.is_walkable(cell_at(vec2(500.0, 500.0))));
+        assert!(!grid.is_walkable(cell_at(vec2(2500.0, 0.0))));
+        assert!(!grid.castle_edge.is_empty());
+        assert!(grid.castle_edge.iter().all(|cell| grid.is_walkable(*cell)));
+    }
+
+    #[test]
+    fn flow_leads_to_the_castle() {
+        let grid = NavGrid::new(&open_area(), Vec2::ZERO);
+        let field = FlowField::new(&grid, cell_at(Vec2::ZERO));
+
+        assert_eq!(field.direction(vec2(750.0, 50.0)), Some(vec2(-1.0, 0.0)));
+        assert_eq!(field.direction(vec2(50.0, -750.0)), Some(vec2(0.0, 1.0)));
+        assert_eq!(field.direction(vec2(2500.0, 0.0)), None);
+    }
+
+    #[test]
+    fn flow_goes_around_rocks() {
+        let mut obstacles = open_area();
+        obstacles.insert_chunk(IVec2::ZERO, vec![WALL]);
+        let grid = NavGrid::new(&obstacles, Vec2::ZERO);
+        let field = FlowField::new(&grid, cell_at(Vec2::ZERO));
+
+        let pos = vec2(750.0, 50.0);
+        let dir = field.direction(pos).unwrap();
+        assert_ne!(dir, vec2(-1.0, 0.0));
+        assert!(grid.is_walkable(cell_at(pos + dir * NAV_CELL_SIZE)));
+    }
+
+    #[test]
+    fn flow_field_is_recomputed_when_rocks_change() {
+        let mut world = World::new();
+        world.insert_resource(open_area());
+        world.init_resource::<NavGrid>();
+        world.init_resource::<FlowField>();
+        world.spawn((Castle, Transform::default()));
+
+        let pos = vec2(750.0, 50.0);
+        world.run_system_once(update_flow_field);
+        assert_eq!(
+            world.resource::<FlowField>().direction(pos),
+            Some(vec2(-1.0, 0.0))
+        );
+
+        world
+            .resource_mut::<Obstacles>()
+            .insert_chunk(IVec2::ZERO, vec![WALL]);
+        world.run_system_once(update_flow_field);
+        assert_ne!(
+            world.resource::<FlowField>().direction(pos),
+            Some(vec2(-1.0, 0.0))
+        );
+    }
+}
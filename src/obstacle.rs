@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use bevy::math::vec2;
+use bevy::prelude::*;
+use kd_tree::{KdPoint, KdTree};
+use rand::Rng;
+
+use crate::chunk::{chunk_origin, WorldSeed, OBSTACLE_SEED_LAYER};
+use crate::*;
+
+/// Opaque pixel size of each rock in `rocks.png`.
+const ROCK_BOUNDS: [(f32, f32); ROCK_SPRITE_COUNT] = [
+    (182., 192.),
+    (180., 196.),
+    (270., 234.),
+    (180., 192.),
+    (182., 192.),
+    (214., 204.),
+    (180., 198.),
+    (180., 194.),
+    (282., 208.),
+    (182., 192.),
+    (180., 196.),
+    (176., 192.),
+    (198., 180.),
+    (180., 192.),
+    (182., 192.),
+    (182., 192.),
+    (180., 196.),
+    (186., 192.),
+    (180., 208.),
+    (182., 192.),
+    (342., 270.),
+    (286., 214.),
+    (174., 236.),
+    (188., 198.),
+];
+
+/// Rocks much wider than tall are slabs; the rest are close enough to round.
+const ROCK_BOX_ASPECT: f32 = 1.1;
+/// Shrinks colliders to the solid core of the sprite so edges don't feel sticky.
+const ROCK_COLLIDER_FIT: f32 = 0.85;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Collider {
+    Circle { radius: f32 },
+    Box { half_size: Vec2 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Obstacle {
+    pub pos: Vec2,
+    pub sprite_index: usize,
+    pub collider: Collider,
+}
+
+struct ObstaclePoint {
+    pos: Vec2,
+    index: usize,
+}
+
+/// Rocks of the loaded chunks; they only collide while their chunk is loaded.
+#[derive(Resource)]
+pub struct Obstacles {
+    chunks: HashMap<IVec2, Vec<Obstacle>>,
+    list: Vec<Obstacle>,
+    tree: KdTree<ObstaclePoint>,
+}
+
+impl KdPoint for ObstaclePoint {
+    type Scalar = f32;
+    type Dim = typenum::U2;
+    fn at(&self, k: usize) -> f32 {
+        if k == 0 {
+            return self.pos.x;
+        }
+
+        self.pos.y
+    }
+}
+
+impl Default for Obstacles {
+    fn default() -> Self {
+        Self {
+            chunks: HashMap::new(),
+            list: Vec::new(),
+            tree: KdTree::build_by_ordered_float(vec![]),
+        }
+    }
+}
+
+impl Collider {
+    fn for_rock(sprite_index: usize) -> Self {
+        let (w, h) = ROCK_BOUNDS[sprite_index];
+        let size = vec2(w, h) * ROCK_SPRITE_SCALE_FACTOR * ROCK_COLLIDER_FIT;
+
+        if w / h > ROCK_BOX_ASPECT {
+            Collider::Box {
+                half_size: size / 2.0,
+            }
+        } else {
+            Collider::Circle {
+                radius: (size.x + size.y) / 4.0,
+            }
+        }
+    }
+
+    pub fn contains(&self, center: Vec2, point: Vec2) -> bool {
+        let local = point - center;
+        match *self {
+            Collider::Circle { radius } => local.length_squared() <= radius * radius,
+            Collider::Box { half_size } => {
+                local.x.abs() <= half_size.x && local.y.abs() <= half_size.y
+            }
+        }
+    }
+
+    /// Moves a circle of `radius` at `pos` out of this collider, if they overlap.
+    pub fn push_out(&self, center: Vec2, pos: Vec2, radius: f32) -> Option<Vec2> {
+        let local = pos - center;
+        match *self {
+            Collider::Circle { radius: own_radius } => {
+                let min_distance = own_radius + radius;
+                let distance = local.length();
+                if distance >= min_distance {
+                    return None;
+                }
+
+                let dir = if distance > 0.0 {
+                    local / distance
+                } else {
+                    Vec2::X
+                };
+                Some(center + dir * min_distance)
+            }
+            Collider::Box { half_size } => {
+                let closest = local.clamp(-half_size, half_size);
+                if closest != local {
+                    let diff = local - closest;
+                    let distance = diff.length();
+                    if distance >= radius {
+                        return None;
+                    }
+
+                    return Some(pos + diff / distance * (radius - distance));
+                }
+
+                // Inside the box: leave through the nearest face.
+                let depth = half_size - local.abs() + radius;
+                if depth.x < depth.y {
+                    Some(pos + vec2(depth.x * local.x.signum(), 0.0))
+                } else {
+                    Some(pos + vec2(0.0, depth.y * local.y.signum()))
+                }
+            }
+        }
+    }
+}
+
+impl Obstacles {
+    pub fn insert_chunk(&mut self, chunk: IVec2, obstacles: Vec<Obstacle>) {
+        self.chunks.insert(chunk, obstacles);
+        self.rebuild();
+    }
+
+    pub fn remove_chunk(&mut self, chunk: IVec2) {
+        if self.chunks.remove(&chunk).is_some() {
+            self.rebuild();
+        }
+    }
+
+    pub fn is_chunk_loaded(&self, chunk: IVec2) -> bool {
+        self.chunks.contains_key(&chunk)
+    }
+
+    pub fn loaded_chunks(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.chunks.keys().copied()
+    }
+
+    fn rebuild(&mut self) {
+        self.list = self.chunks.values().flatten().copied().collect();
+        let points = self
+            .list
+            .iter()
+            .enumerate()
+            .map(|(index, obstacle)| ObstaclePoint {
+                pos: obstacle.pos,
+                index,
+            })
+            .collect();
+        self.tree = KdTree::build_by_ordered_float(points);
+    }
+
+    pub fn near(&self, pos: Vec2, radius: f32) -> impl Iterator<Item = &Obstacle> {
+        self.tree
+            .within_radius(&[pos.x, pos.y], radius + ROCK_MAX_EXTENT)
+            .into_iter()
+            .map(|point| &self.list[point.index])
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        self.near(point, 0.0)
+            .any(|obstacle| obstacle.collider.contains(obstacle.pos, point))
+    }
+
+    /// Pushes a circle of `radius` at `pos` out of every obstacle it overlaps.
+    pub fn resolve(&self, pos: Vec2, radius: f32) -> Vec2 {
+        let mut pos = pos;
+        for obstacle in self.near(pos, radius) {
+            if let Some(resolved) = obstacle.collider.push_out(obstacle.pos, pos, radius) {
+                pos = resolved;
+            }
+        }
+
+        pos
+    }
+}
+
+/// Rocks of one chunk, the same every time it loads for a given seed.
+pub fn generate_chunk_obstacles(seed: &WorldSeed, chunk: IVec2) -> Vec<Obstacle> {
+    let mut rng = seed.chunk_rng(chunk, OBSTACLE_SEED_LAYER);
+    let origin = chunk_origin(chunk);
+
+    let mut list = Vec::new();
+    for _ in 0..ROCKS_PER_CHUNK {
+        let pos = origin
+            + vec2(
+                rng.gen_range(0.0..CHUNK_SIZE),
+                rng.gen_range(0.0..CHUNK_SIZE),
+            );
+        let sprite_index = rng.gen_range(0..ROCK_SPRITE_COUNT);
+
+        if pos.x.abs() > WORLD_W || pos.y.abs() > WORLD_H {
+            continue;
+        }
+        if pos.length() < ROCK_CLEARANCE_AROUND_CASTLE {
+            continue;
+        }
+
+        list.push(Obstacle {
+            pos,
+            sprite_index,
+            collider: Collider::for_rock(sprite_index),
+        });
+    }
+
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CIRCLE: Collider = Collider::Circle { radius: 10.0 };
+    const BOX: Collider = Collider::Box {
+        half_size: Vec2::new(20.0, 10.0),
+    };
+
+    #[test]
+    fn contains_checks_the_collider_shape() {
+        assert!(CIRCLE.contains(Vec2::ZERO, vec2(6.0, 6.0)));
+        assert!(!CIRCLE.contains(Vec2::ZERO, vec2(8.0, 8.0)));
+        assert!(BOX.contains(Vec2::ZERO, vec2(19.0, 9.0)));
+        assert!(!BOX.contains(Vec2::ZERO, vec2(19.0, 11.0)));
+    }
+
+    #[test]
+    fn circle_push_out_keeps_the_combined_radius() {
+        assert_eq!(CIRCLE.push_out(Vec2::ZERO, vec2(20.0, 0.0), 5.0), None);
+
+        let pushed = CIRCLE.push_out(Vec2::ZERO, vec2(0.0, 12.0), 5.0).unwrap();
+        assert!((pushed - vec2(0.0, 15.0)).length() < 1e-4);
+
+        let pushed = CIRCLE.push_out(Vec2::ZERO, Vec2::ZERO, 5.0).unwrap();
+        assert!((pushed - vec2(15.0, 0.0)).length() < 1e-4);
+    }
+
+    #[test]
+    fn box_push_out_clears_the_nearest_face() {
+        assert_eq!(BOX.push_out(Vec2::ZERO, vec2(30.0, 0.0), 5.0), None);
+
+        let pushed = BOX.push_out(Vec2::ZERO, vec2(23.0, 0.0), 5.0).unwrap();
+        assert!((pushed - vec2(25.0, 0.0)).length() < 1e-4);
+
+        let pushed = BOX.push_out(Vec2::ZERO, vec2(5.0, 8.0), 5.0).unwrap();
+        assert!((pushed - vec2(5.0, 15.0)).length() < 1e-4);
+    }
+
+    #[test]
+    fn chunk_rocks_only_collide_while_loaded() {
+        let rock = Obstacle {
+            pos: vec2(500.0, 500.0),
+            sprite_index: 0,
+            collider: CIRCLE,
+        };
+        let mut obstacles = Obstacles::default();
+        obstacles.insert_chunk(IVec2::ZERO, vec![rock]);
+        assert!(obstacles.contains(rock.pos));
+        assert_eq!(obstacles.resolve(rock.pos, 5.0), rock.pos + vec2(15.0, 0.0));
+
+        obstacles.remove_chunk(IVec2::ZERO);
+        assert!(!obstacles.contains(rock.pos));
+    }
+
+    #[test]
+    fn chunk_rocks_are_seeded() {
+        let seed = WorldSeed(7);
+        let chunk = IVec2::new(3, -2);
+        let first = generate_chunk_obstacles(&seed, chunk);
+        let second = generate_chunk_obstacles(&seed, chunk);
+
+        assert_eq!(first.len(), second.len());
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert_eq!(a.pos, b.pos);
+            assert_eq!(a.sprite_index, b.sprite_index);
+        }
+    }
+}
@@ -6,7 +6,7 @@ use bevy::{
     window::PrimaryWindow,
 };
 
-use crate::state::GameState;
+use crate::state::playing;
 
 /// Plugin that adds the necessary systems for `PanCam` components to work
 #[derive(Default)]
@@ -21,7 +21,7 @@ impl Plugin for PanCamPlugin {
         app.add_systems(
             Update,
             (camera_movement, camera_zoom)
-            .run_if(in_state(GameState::InGame))
+            .run_if(playing)
             .in_set(PanCamSystemSet)
         )
         .register_type::<PanCam>();
@@ -1,5 +1,10 @@
 use bevy::{math::vec3, prelude::*};
-use crate::state::GameState;
+use crate::audio::{Sfx, SfxEvent};
+use crate::damage::DeathEvent;
+use crate::gamepad::ActiveGamepad;
+use crate::input::{ActionState, InputAction};
+use crate::obstacle::Obstacles;
+use crate::state::{playing, GameState};
 use crate::*;
 
 #[derive(Component)]
@@ -14,100 +19,109 @@ pub enum PlayerState {
     Run,
 }
 
-#[derive(Event)]
-pub struct PlayerEnemyCollisionEvent;
-
 #[derive(Component)]
-pub struct Health(pub f32);
+pub struct GoldCount(pub f32);
 
 #[derive(Component)]
-pub struct GoldCount(pub f32);
+pub struct Dash {
+    pub duration: Timer,
+    pub cooldown: Timer,
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        let mut duration = Timer::from_seconds(PLAYER_DASH_TIME_SECS, TimerMode::Once);
+        let mut cooldown = Timer::from_seconds(PLAYER_DASH_COOLDOWN_SECS, TimerMode::Once);
+        duration.tick(duration.duration());
+        cooldown.tick(cooldown.duration());
+
+        Self { duration, cooldown }
+    }
+}
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<PlayerEnemyCollisionEvent>()
-        .add_systems(
+        app.add_systems(
             Update,
             (
                 handle_player_death,
                 handle_player_input,
-                handle_player_enemy_collision_events,
             )
-            .run_if(in_state(GameState::InGame)),
+            .run_if(playing),
         );
     }
 }
 
-fn handle_player_enemy_collision_events(
-    mut player_query: Query<&mut Health, With<Player>>,
-    mut events: EventReader<PlayerEnemyCollisionEvent>,
-) {
-    if player_query.is_empty() {
-        return;
-    }
-
-    let mut health = player_query.single_mut();
-    for _ in events.read() {
-        health.0 -= ENEMY_DAMAGE;
-    }
-}
-
 fn handle_player_death(
-    player_query: Query<&Health, With<Player>>,
+    player_query: Query<(), With<Player>>,
+    mut death_events: EventReader<DeathEvent>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut sfx_events: EventWriter<SfxEvent>,
 ) {
-    if player_query.is_empty() {
-        return;
-    }
-
-    let health = player_query.single();
-
-    if health.0 <= 0.0 {
-        next_state.set(GameState::MainMenu);
+    for event in death_events.read() {
+        if player_query.contains(event.entity) {
+            sfx_events.send(SfxEvent::new(Sfx::GameOver));
+            next_state.set(GameState::MainMenu);
+        }
     }
 }
 
 fn handle_player_input(
-    mut player_query: Query<(&mut Transform, &mut PlayerState), With<Player>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut player_query: Query<(&mut Transform, &mut PlayerState, &mut Dash), With<Player>>,
+    actions: Res<ActionState>,
+    obstacles: Res<Obstacles>,
+    active_gamepad: Res<ActiveGamepad>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    time: Res<Time>,
 ) {
     if player_query.is_empty() {
         return;
     }
-    let (mut transform, mut player_state) = player_query.single_mut();
-
-    let w_key: bool =
-        keyboard_input.pressed(KeyCode::KeyW) || keyboard_input.pressed(KeyCode::ArrowUp);
-    let s_key: bool =
-        keyboard_input.pressed(KeyCode::KeyS) || keyboard_input.pressed(KeyCode::ArrowDown);
-    let a_key: bool =
-        keyboard_input.pressed(KeyCode::KeyA) || keyboard_input.pressed(KeyCode::ArrowLeft);
-    let d_key: bool =
-        keyboard_input.pressed(KeyCode::KeyD) || keyboard_input.pressed(KeyCode::ArrowRight);
+    let (mut transform, mut player_state, mut dash) = player_query.single_mut();
+    dash.duration.tick(time.delta());
+    dash.cooldown.tick(time.delta());
 
     let mut delta = Vec2::ZERO;
-    if w_key {
+    if actions.pressed(InputAction::MoveUp) {
         delta.y += 1.0;
     }
-    if s_key {
+    if actions.pressed(InputAction::MoveDown) {
         delta.y -= 1.0;
     }
-    if a_key {
+    if actions.pressed(InputAction::MoveLeft) {
         delta.x -= 1.0;
     }
-    if d_key {
+    if actions.pressed(InputAction::MoveRight) {
         delta.x += 1.0;
     }
 
-    if delta.is_finite()
-        && (delta.x.abs() > 0. || delta.y.abs() > 0.)
-        && (w_key || s_key || a_key || d_key)
-    {
-        transform.translation += vec3(delta.x, delta.y, 0.).normalize() * PLAYER_SPEED;
-        *player_state = PlayerState::Run;
+    let movement = if delta != Vec2::ZERO {
+        delta.normalize()
     } else {
+        active_gamepad.left_stick(&gamepad_axes)
+    };
+
+    if movement == Vec2::ZERO {
         *player_state = PlayerState::Idle;
+        return;
     }
+
+    if actions.just_pressed(InputAction::Dash) && dash.cooldown.finished() {
+        dash.duration.reset();
+        dash.cooldown.reset();
+    }
+
+    let speed = if dash.duration.finished() {
+        PLAYER_SPEED
+    } else {
+        PLAYER_SPEED * PLAYER_DASH_SPEED_MULTIPLIER
+    };
+
+    transform.translation += vec3(movement.x, movement.y, 0.) * speed;
+    let pos = obstacles.resolve(transform.translation.truncate(), PLAYER_COLLISION_RADIUS);
+    transform.translation.x = pos.x.clamp(-WORLD_W, WORLD_W);
+    transform.translation.y = pos.y.clamp(-WORLD_H, WORLD_H);
+    *player_state = PlayerState::Run;
 }
@@ -0,0 +1,218 @@
+use bevy::math::vec2;
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use bevy::utils::Duration;
+use rand::Rng;
+
+use crate::audio::{Sfx, SfxEvent};
+use crate::damage::{Damage, DamageEvent, DeathEvent, Health};
+use crate::director::Director;
+use crate::enemy::spawn_random_enemy;
+use crate::gun::{Bullet, Faction};
+use crate::obstacle::Obstacles;
+use crate::player::Player;
+use crate::state::{playing, GameState};
+use crate::world::{random_position_around, GameEntity};
+use crate::*;
+
+pub struct PortalPlugin;
+
+/// A spawner enemies emerge from after a short warning; destroyable by the player.
+#[derive(Component)]
+pub struct Portal {
+    pub pending: usize,
+    warning: Timer,
+}
+
+impl Plugin for PortalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::InGame), spawn_initial_portals)
+            .add_systems(
+                Update,
+                (
+                    maintain_portals.run_if(on_timer(Duration::from_secs_f32(PORTAL_RESPAWN_SECS))),
+                    update_portals,
+                    handle_portal_bullet_collision,
+                    despawn_destroyed_portals,
+                )
+                    .run_if(playing),
+            );
+    }
+}
+
+impl Portal {
+    /// Queues enemies to emerge once the warning has played out.
+    pub fn queue(&mut self, count: usize) {
+        if self.pending == 0 {
+            self.warning.reset();
+        }
+        self.pending += count;
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.pending > 0
+    }
+}
+
+impl Default for Portal {
+    fn default() -> Self {
+        Self {
+            pending: 0,
+            warning: Timer::from_seconds(PORTAL_WARNING_SECS, TimerMode::Once),
+        }
+    }
+}
+
+fn spawn_initial_portals(
+    mut commands: Commands,
+    handle: Res<GlobalTextureAtlas>,
+    obstacles: Res<Obstacles>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let player_pos = player_transform.translation.truncate();
+    for _ in 0..PORTAL_COUNT {
+        spawn_portal(&mut commands, &handle, &obstacles, player_pos);
+    }
+}
+
+/// Drops portals the player has left far behind and tops the count back up one at a time.
+fn maintain_portals(
+    mut commands: Commands,
+    handle: Res<GlobalTextureAtlas>,
+    obstacles: Res<Obstacles>,
+    player_query: Query<&Transform, With<Player>>,
+    portal_query: Query<(Entity, &Transform, &Portal), Without<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let player_pos = player_transform.translation.truncate();
+    let mut count = 0;
+    for (entity, transform, portal) in portal_query.iter() {
+        let distance = transform.translation.truncate().distance(player_pos);
+        if distance > PORTAL_FORGET_DISTANCE && !portal.is_warning() {
+            commands.entity(entity).despawn();
+        } else {
+            count += 1;
+        }
+    }
+
+    if count < PORTAL_COUNT {
+        spawn_portal(&mut commands, &handle, &obstacles, player_pos);
+    }
+}
+
+fn spawn_portal(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    obstacles: &Obstacles,
+    player_pos: Vec2,
+) {
+    let pos = random_position_around(player_pos, PORTAL_MIN_DISTANCE, PORTAL_MAX_DISTANCE);
+    let pos = obstacles.resolve(pos, PORTAL_HIT_RADIUS);
+
+    commands.spawn((
+        SpriteSheetBundle {
+            texture: handle.image.clone().unwrap(),
+            atlas: TextureAtlas {
+                layout: handle.layout.clone().unwrap(),
+                index: PORTAL_SPRITE_INDEX,
+            },
+            sprite: Sprite {
+                color: PORTAL_IDLE_COLOR,
+                ..default()
+            },
+            transform: Transform::from_translation(pos.extend(0.5))
+                .with_scale(Vec3::splat(PORTAL_SPRITE_SCALE_FACTOR)),
+            ..default()
+        },
+        Portal::default(),
+        Health::new(PORTAL_HEALTH),
+        GameEntity,
+    ));
+}
+
+fn update_portals(
+    mut commands: Commands,
+    time: Res<Time>,
+    handle: Res<GlobalTextureAtlas>,
+    obstacles: Res<Obstacles>,
+    director: Res<Director>,
+    mut portal_query: Query<(&mut Portal, &mut Transform, &mut Sprite)>,
+    mut sfx_events: EventWriter<SfxEvent>,
+) {
+    for (mut portal, mut transform, mut sprite) in portal_query.iter_mut() {
+        if !portal.is_warning() {
+            sprite.color = PORTAL_IDLE_COLOR;
+            transform.scale = Vec3::splat(PORTAL_SPRITE_SCALE_FACTOR);
+            continue;
+        }
+
+        // Pulse faster and redder as the enemies are about to emerge.
+        let progress = portal.warning.tick(time.delta()).fraction();
+        let pulse = (portal.warning.elapsed_secs() * (8.0 + progress * 16.0)).sin() * 0.5 + 0.5;
+        sprite.color = PORTAL_IDLE_COLOR * (1.0 - progress) + PORTAL_WARNING_COLOR * progress;
+        transform.scale = Vec3::splat(PORTAL_SPRITE_SCALE_FACTOR * (1.0 + pulse * 0.3));
+
+        if !portal.warning.finished() {
+            continue;
+        }
+
+        let pos = transform.translation.truncate();
+        let mut rng = rand::thread_rng();
+        for _ in 0..portal.pending {
+            let offset = vec2(
+                rng.gen_range(-PORTAL_SPAWN_SCATTER..PORTAL_SPAWN_SCATTER),
+                rng.gen_range(-PORTAL_SPAWN_SCATTER..PORTAL_SPAWN_SCATTER),
+            );
+            let enemy_pos = obstacles.resolve(pos + offset, ENEMY_COLLISION_RADIUS);
+            spawn_random_enemy(&mut commands, &handle, enemy_pos, director.elite_chance());
+        }
+
+        portal.pending = 0;
+        sfx_events.send(SfxEvent::at(Sfx::EnemySpawn, pos));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_portal_bullet_collision(
+    mut commands: Commands,
+    bullet_query: Query<(&Transform, Entity, &Faction, &Damage), With<Bullet>>,
+    portal_query: Query<(Entity, &Transform), (With<Portal>, Without<Bullet>)>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut sfx_events: EventWriter<SfxEvent>,
+) {
+    for (portal, portal_transform) in portal_query.iter() {
+        let portal_pos = portal_transform.translation.truncate();
+        for (bullet_transform, bullet, faction, damage) in bullet_query.iter() {
+            if *faction != Faction::Player
+                || bullet_transform.translation.truncate().distance(portal_pos) > PORTAL_HIT_RADIUS
+            {
+                continue;
+            }
+
+            damage_events.send(DamageEvent::new(portal, None, *damage));
+            commands.entity(bullet).despawn();
+            sfx_events.send(SfxEvent::at(Sfx::EnemyHit, portal_pos));
+        }
+    }
+}
+
+fn despawn_destroyed_portals(
+    mut commands: Commands,
+    portal_query: Query<&Transform, With<Portal>>,
+    mut death_events: EventReader<DeathEvent>,
+    mut sfx_events: EventWriter<SfxEvent>,
+) {
+    for event in death_events.read() {
+        if let Ok(transform) = portal_query.get(event.entity) {
+            commands.entity(event.entity).despawn();
+            sfx_events.send(SfxEvent::at(Sfx::EnemyDeath, transform.translation.truncate()));
+        }
+    }
+}
@@ -1,12 +1,14 @@
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 
+use crate::gamepad::GamepadAim;
+use crate::player::Player;
 use crate::state::GameState;
 use crate::*;
 
 pub struct ResourcesPlugin;
 
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct GlobalTextureAtlas {
     pub layout: Option<Handle<TextureAtlasLayout>>,
     pub image: Option<Handle<Image>>,
@@ -24,6 +26,12 @@ pub struct GlobalTextureAtlas {
     pub button_image: Option<Handle<Image>>,
     pub castle_layout: Option<Handle<TextureAtlasLayout>>,
     pub castle_image: Option<Handle<Image>>,
+    pub rock_layout: Option<Handle<TextureAtlasLayout>>,
+    pub rock_image: Option<Handle<Image>>,
+    pub crystal_layout: Option<Handle<TextureAtlasLayout>>,
+    pub crystal_image: Option<Handle<Image>>,
+    pub explosion_layout: Option<Handle<TextureAtlasLayout>>,
+    pub explosion_image: Option<Handle<Image>>,
 }
 #[derive(Resource)]
 pub struct CursorPosition(pub Option<Vec2>);
@@ -123,6 +131,39 @@ fn load_assets(
     );
     handle.castle_layout = Some(texture_atlas_layouts.add(castle_layout));
 
+    handle.rock_image = Some(asset_server.load(ROCK_SPRITE_SHEET_PATH));
+
+    let rock_layout = TextureAtlasLayout::from_grid(
+        Vec2::new(ROCK_TILE_W as f32, ROCK_TILE_H as f32),
+        ROCK_SPRITE_SHEET_W,
+        ROCK_SPRITE_SHEET_H,
+        None,
+        None,
+    );
+    handle.rock_layout = Some(texture_atlas_layouts.add(rock_layout));
+
+    handle.crystal_image = Some(asset_server.load(CRYSTAL_SPRITE_SHEET_PATH));
+
+    let crystal_layout = TextureAtlasLayout::from_grid(
+        Vec2::new(CRYSTAL_TILE_W as f32, CRYSTAL_TILE_H as f32),
+        CRYSTAL_SPRITE_SHEET_W,
+        CRYSTAL_SPRITE_SHEET_H,
+        None,
+        None,
+    );
+    handle.crystal_layout = Some(texture_atlas_layouts.add(crystal_layout));
+
+    handle.explosion_image = Some(asset_server.load(EXPLOSION_SPRITE_SHEET_PATH));
+
+    let explosion_layout = TextureAtlasLayout::from_grid(
+        Vec2::new(EXPLOSION_TILE_W as f32, EXPLOSION_TILE_H as f32),
+        EXPLOSION_SPRITE_SHEET_W,
+        EXPLOSION_SPRITE_SHEET_H,
+        None,
+        None,
+    );
+    handle.explosion_layout = Some(texture_atlas_layouts.add(explosion_layout));
+
     handle.gun_image = Some(asset_server.load(GUN_SPRITE_SHEET_PATH));
 
     let gun_layout = TextureAtlasLayout::from_grid(
@@ -141,9 +182,17 @@ fn update_cursor_position(
     mut cursor_pos: ResMut<CursorPosition>,
     window_query: Query<&Window, With<PrimaryWindow>>,
     camera_query: Query<(&Camera, &GlobalTransform), With<Camera>>,
+    player_query: Query<&Transform, With<Player>>,
+    gamepad_aim: Res<GamepadAim>,
 ) {
     if window_query.is_empty() || camera_query.is_empty() {
         cursor_pos.0 = None;
+        return;
+    }
+
+    if let (Some(dir), Ok(player_transform)) = (gamepad_aim.0, player_query.get_single()) {
+        cursor_pos.0 = Some(player_transform.translation.truncate() + dir * GAMEPAD_AIM_DISTANCE);
+        return;
     }
 
     let (camera, camera_transform) = camera_query.single();
@@ -153,26 +202,3 @@ fn update_cursor_position(
         .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
         .map(|ray| ray.origin.truncate());
 }
-
-impl Default for GlobalTextureAtlas {
-    fn default() -> Self {
-        Self {
-            layout: None,
-            image: None,
-            player_layout: None,
-            player_image: None,
-            gun_image: None,
-            gun_layout: None,
-            coin_layout: None,
-            coin_image: None,
-            menu_layout: None,
-            menu_image: None,
-            cursor_image: None,
-            cursor_layout: None,
-            button_image: None,
-            button_layout: None,
-            castle_image: None,
-            castle_layout: None,
-        }   
-    }
-}
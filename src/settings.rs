@@ -0,0 +1,274 @@
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{load_config, save_config};
+use crate::difficulty::Difficulty;
+use crate::*;
+
+pub struct SettingsPlugin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowModeSetting {
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub window_mode: WindowModeSetting,
+    pub resolution: (f32, f32),
+    pub vsync: bool,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub difficulty: Difficulty,
+    pub adaptive_director: bool,
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .add_systems(Update, apply_window_settings);
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            window_mode: WindowModeSetting::Windowed,
+            resolution: (WW, WH),
+            vsync: true,
+            min_zoom: CAMERA_MIN_ZOOM,
+            max_zoom: CAMERA_MAX_ZOOM,
+            master_volume: 1.0,
+            music_volume: 0.6,
+            sfx_volume: 0.8,
+            difficulty: Difficulty::Normal,
+            adaptive_director: true,
+        }
+    }
+}
+
+impl WindowModeSetting {
+    pub fn label(&self) -> &'static str {
+        match self {
+            WindowModeSetting::Windowed => "Windowed",
+            WindowModeSetting::Borderless => "Borderless",
+            WindowModeSetting::Fullscreen => "Fullscreen",
+        }
+    }
+}
+
+impl Settings {
+    pub fn window_mode(&self) -> WindowMode {
+        match self.window_mode {
+            WindowModeSetting::Windowed => WindowMode::Windowed,
+            WindowModeSetting::Borderless => WindowMode::BorderlessFullscreen,
+            WindowModeSetting::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+
+    pub fn cycle_window_mode(&mut self) {
+        self.window_mode = match self.window_mode {
+            WindowModeSetting::Windowed => WindowModeSetting::Borderless,
+            WindowModeSetting::Borderless => WindowModeSetting::Fullscreen,
+            WindowModeSetting::Fullscreen => WindowModeSetting::Windowed,
+        };
+    }
+
+    pub fn cycle_resolution(&mut self) {
+        let index = RESOLUTIONS
+            .iter()
+            .position(|resolution| *resolution == self.resolution)
+            .map_or(0, |index| (index + 1) % RESOLUTIONS.len());
+        self.resolution = RESOLUTIONS[index];
+    }
+
+    pub fn cycle_min_zoom(&mut self) {
+        self.min_zoom = next_zoom_level(self.min_zoom, |zoom| zoom < self.max_zoom);
+    }
+
+    pub fn cycle_max_zoom(&mut self) {
+        self.max_zoom = next_zoom_level(self.max_zoom, |zoom| zoom > self.min_zoom);
+    }
+
+    pub fn cycle_master_volume(&mut self) {
+        self.master_volume = next_volume_level(self.master_volume);
+    }
+
+    pub fn cycle_music_volume(&mut self) {
+        self.music_volume = next_volume_level(self.music_volume);
+    }
+
+    pub fn cycle_sfx_volume(&mut self) {
+        self.sfx_volume = next_volume_level(self.sfx_volume);
+    }
+
+    pub fn cycle_difficulty(&mut self) {
+        self.difficulty = self.difficulty.next();
+    }
+
+    pub fn music_level(&self) -> f32 {
+        self.master_volume * self.music_volume
+    }
+
+    pub fn sfx_level(&self) -> f32 {
+        self.master_volume * self.sfx_volume
+    }
+
+    /// Replaces values a hand-edited settings file could break the game with.
+    fn sanitized(mut self) -> Self {
+        let defaults = Settings::default();
+
+        let valid_zoom = |zoom: f32| zoom.is_finite() && zoom > 0.0;
+        if !valid_zoom(self.min_zoom) {
+            self.min_zoom = defaults.min_zoom;
+        }
+        if !valid_zoom(self.max_zoom) {
+            self.max_zoom = defaults.max_zoom;
+        }
+        if self.min_zoom > self.max_zoom {
+            std::mem::swap(&mut self.min_zoom, &mut self.max_zoom);
+        }
+
+        let (width, height) = self.resolution;
+        if !(width.is_finite() && height.is_finite() && width >= 1.0 && height >= 1.0) {
+            self.resolution = defaults.resolution;
+        }
+
+        for (volume, default) in [
+            (&mut self.master_volume, defaults.master_volume),
+            (&mut self.music_volume, defaults.music_volume),
+            (&mut self.sfx_volume, defaults.sfx_volume),
+        ] {
+            *volume = if volume.is_nan() {
+                default
+            } else {
+                volume.clamp(0.0, 1.0)
+            };
+        }
+
+        self
+    }
+}
+
+fn next_volume_level(current: f32) -> f32 {
+    let volume = current + VOLUME_STEP;
+    if volume > 1.0 + f32::EPSILON {
+        0.0
+    } else {
+        volume
+    }
+}
+
+fn next_zoom_level(current: f32, allowed: impl Fn(f32) -> bool) -> f32 {
+    let levels: Vec<f32> = ZOOM_LEVELS.iter().copied().filter(|zoom| allowed(*zoom)).collect();
+
+    levels
+        .iter()
+        .copied()
+        .find(|zoom| *zoom > current)
+        .or(levels.first().copied())
+        .unwrap_or(current)
+}
+
+pub fn load_settings() -> Settings {
+    load_config::<Settings>(SETTINGS_FILE_NAME)
+        .unwrap_or_default()
+        .sanitized()
+}
+
+pub fn save_settings(settings: &Settings) {
+    save_config(SETTINGS_FILE_NAME, settings);
+}
+
+/// Only touches the window when a video option changed, so other settings
+/// don't undo a manual resize.
+#[allow(clippy::type_complexity)]
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut applied: Local<Option<(WindowModeSetting, (f32, f32), bool)>>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let video = (settings.window_mode, settings.resolution, settings.vsync);
+    if *applied == Some(video) {
+        return;
+    }
+    *applied = Some(video);
+
+    for mut window in window_query.iter_mut() {
+        window.mode = settings.window_mode();
+        window.present_mode = settings.present_mode();
+        window.resolution.set(settings.resolution.0, settings.resolution.1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitized_keeps_valid_settings() {
+        assert_eq!(Settings::default().sanitized(), Settings::default());
+    }
+
+    #[test]
+    fn sanitized_resets_invalid_zoom_and_orders_the_range() {
+        let settings = Settings {
+            min_zoom: f32::NAN,
+            max_zoom: -1.0,
+            ..default()
+        }
+        .sanitized();
+        assert_eq!(settings.min_zoom, CAMERA_MIN_ZOOM);
+        assert_eq!(settings.max_zoom, CAMERA_MAX_ZOOM);
+
+        let settings = Settings {
+            min_zoom: 3.0,
+            max_zoom: 1.0,
+            ..default()
+        }
+        .sanitized();
+        assert_eq!((settings.min_zoom, settings.max_zoom), (1.0, 3.0));
+    }
+
+    #[test]
+    fn sanitized_resets_invalid_resolution() {
+        let settings = Settings {
+            resolution: (0.0, f32::INFINITY),
+            ..default()
+        }
+        .sanitized();
+        assert_eq!(settings.resolution, (WW, WH));
+    }
+
+    #[test]
+    fn sanitized_clamps_volumes() {
+        let settings = Settings {
+            master_volume: 2.0,
+            music_volume: -1.0,
+            sfx_volume: f32::NAN,
+            ..default()
+        }
+        .sanitized();
+        assert_eq!(settings.master_volume, 1.0);
+        assert_eq!(settings.music_volume, 0.0);
+        assert_eq!(settings.sfx_volume, Settings::default().sfx_volume);
+    }
+}
@@ -6,5 +6,15 @@ pub enum GameState {
     Loading,
     GameInit,
     MainMenu,
+    Controls,
+    Settings,
     InGame,
-}
\ No newline at end of file
+}
+/// Set while the in-game pause menu is open; gameplay systems stop without leaving `InGame`.
+#[derive(Resource, Default)]
+pub struct Paused(pub bool);
+
+/// Run condition for gameplay systems: in game and not paused.
+pub fn playing(state: Res<State<GameState>>, paused: Res<Paused>) -> bool {
+    *state.get() == GameState::InGame && !paused.0
+}
@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+
+use crate::damage::{Damage, DamageEvent};
+use crate::elite::EliteAffixes;
+use crate::enemy::Enemy;
+use crate::state::playing;
+use crate::*;
+
+pub struct StatusPlugin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusKind {
+    Burn,
+    Slow,
+    Poison,
+    Freeze,
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub damage_per_second: f32,
+    pub speed_multiplier: f32,
+    pub duration: Timer,
+}
+
+/// Every status currently active on an entity, at most one per kind.
+#[derive(Component, Debug, Clone, Default)]
+pub struct StatusEffects(pub Vec<StatusEffect>);
+
+/// Inflicts a status on whatever this projectile or hazard hits.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct OnHitStatus(pub StatusKind);
+
+/// Entry point for weapons, towers and traps alike.
+#[derive(Event)]
+pub struct ApplyStatusEvent {
+    pub target: Entity,
+    pub kind: StatusKind,
+}
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ApplyStatusEvent>().add_systems(
+            Update,
+            (apply_status_events, update_status_effects)
+                .chain()
+                .run_if(playing),
+        );
+    }
+}
+
+impl StatusKind {
+    pub const ALL: [StatusKind; 4] = [
+        StatusKind::Burn,
+        StatusKind::Slow,
+        StatusKind::Poison,
+        StatusKind::Freeze,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StatusKind::Burn => "Burn",
+            StatusKind::Slow => "Slow",
+            StatusKind::Poison => "Poison",
+            StatusKind::Freeze => "Freeze",
+        }
+    }
+
+    pub fn tint(&self) -> Color {
+        match self {
+            StatusKind::Burn => Color::rgb(1.0, 0.5, 0.2),
+            StatusKind::Slow => Color::rgb(0.6, 0.6, 1.0),
+            StatusKind::Poison => Color::rgb(0.4, 1.0, 0.3),
+            StatusKind::Freeze => Color::rgb(0.5, 1.0, 1.0),
+        }
+    }
+
+    pub fn effect(&self) -> StatusEffect {
+        let (damage_per_second, speed_multiplier, secs) = match self {
+            StatusKind::Burn => (BURN_DAMAGE_PER_SECOND, 1.0, BURN_TIME_SECS),
+            StatusKind::Slow => (0.0, SLOW_SPEED_MULTIPLIER, SLOW_TIME_SECS),
+            StatusKind::Poison => (POISON_DAMAGE_PER_SECOND, 1.0, POISON_TIME_SECS),
+            StatusKind::Freeze => (0.0, 0.0, FREEZE_TIME_SECS),
+        };
+
+        StatusEffect {
+            kind: *self,
+            damage_per_second,
+            speed_multiplier,
+            duration: Timer::from_seconds(secs, TimerMode::Once),
+        }
+    }
+}
+
+impl StatusEffects {
+    /// Re-applying a kind that is already active refreshes its duration.
+    pub fn apply(&mut self, effect: StatusEffect) {
+        match self.0.iter_mut().find(|active| active.kind == effect.kind) {
+            Some(active) => *active = effect,
+            None => self.0.push(effect),
+        }
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.0.iter().any(|effect| effect.kind == kind)
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        self.0
+            .iter()
+            .map(|effect| effect.speed_multiplier)
+            .product()
+    }
+
+    pub fn tint(&self) -> Option<Color> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        let sum = self
+            .0
+            .iter()
+            .map(|effect| Vec4::from(effect.kind.tint().as_rgba_f32()))
+            .sum::<Vec4>();
+
+        Some(Color::rgba_from_array(sum / self.0.len() as f32))
+    }
+}
+
+fn apply_status_events(
+    mut commands: Commands,
+    mut events: EventReader<ApplyStatusEvent>,
+    mut status_query: Query<Option<&mut StatusEffects>, With<Enemy>>,
+) {
+    for event in events.read() {
+        let Ok(statuses) = status_query.get_mut(event.target) else {
+            continue;
+        };
+
+        match statuses {
+            Some(mut statuses) => statuses.apply(event.kind.effect()),
+            None => {
+                commands
+                    .entity(event.target)
+                    .try_insert(StatusEffects(vec![event.kind.effect()]));
+            }
+        }
+    }
+}
+
+fn update_status_effects(
+    time: Res<Time>,
+    mut status_query: Query<(
+        Entity,
+        &mut StatusEffects,
+        &mut Sprite,
+        Option<&EliteAffixes>,
+    )>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity, mut statuses, mut sprite, affixes) in status_query.iter_mut() {
+        for effect in statuses.0.iter_mut() {
+            effect.duration.tick(time.delta());
+            if effect.damage_per_second > 0.0 {
+                let damage = Damage::new(
+                    effect.damage_per_second * time.delta_seconds(),
+                    effect.kind.into(),
+                );
+                damage_events.send(DamageEvent::new(entity, None, damage));
+            }
+        }
+        statuses.0.retain(|effect| !effect.duration.finished());
+
+        let color = statuses
+            .tint()
+            .unwrap_or_else(|| affixes.map_or(Color::WHITE, EliteAffixes::tint));
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
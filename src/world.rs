@@ -1,13 +1,20 @@
 use self::{
-    gun::{Gun, GunTimer},
+    gun::{Gun, GunAmmo, GunTimer, WeaponStats},
     player::Player,
     state::GameState,
 };
 use crate::*;
 use animation::AnimationTimer;
-use bevy::{math::vec3, prelude::*, time::Stopwatch};
-use player::{GoldCount, Health, PlayerState};
+use explosion::RocketLauncher;
+use bevy::{
+    math::{vec2, vec3},
+    prelude::*,
+    time::Stopwatch,
+};
+use damage::{Armor, Health, Shield};
+use player::{Dash, GoldCount, PlayerState};
 use rand::Rng;
+use std::f32::consts::PI;
 
 #[derive(Component)]
 pub struct GameEntity;
@@ -17,7 +24,7 @@ impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             OnEnter(GameState::GameInit),
-            (init_world, spawn_world_decorrations),
+            (init_world, spawn_world_border),
         )
         .add_systems(OnExit(GameState::InGame), despawn_all_game_entities);
     }
@@ -42,9 +49,12 @@ fn init_world(
         Player {
             attacks: false,
         },
-        Health(PLAYER_HEALTH),
+        Health::new(PLAYER_HEALTH),
+        Armor(PLAYER_ARMOR),
+        Shield::new(PLAYER_SHIELD),
         GoldCount(0.),
         PlayerState::default(),
+        Dash::default(),
         AnimationTimer(Timer::from_seconds(0.15, TimerMode::Repeating)),
         GameEntity,
     ));
@@ -62,29 +72,35 @@ fn init_world(
         Gun,
         AnimationTimer(Timer::from_seconds(0.013, TimerMode::Repeating)),
         GunTimer(Stopwatch::new()),
+        GunAmmo::default(),
+        WeaponStats::default(),
+        RocketLauncher::default(),
         GameEntity,
     ));
 
     next_state.set(GameState::InGame);
 }
 
-fn spawn_world_decorrations(mut commands: Commands, handle: Res<GlobalTextureAtlas>) {
-    let mut rng = rand::thread_rng();
-
-    for _ in 0..NUM_DECORRATIONS {
-        let x = rng.gen_range(-WORLD_W..WORLD_W);
-        let y = rng.gen_range(-WORLD_H..WORLD_H);
+fn spawn_world_border(mut commands: Commands) {
+    let half_thickness = WORLD_BORDER_THICKNESS / 2.0;
+    let width = (WORLD_W + WORLD_BORDER_THICKNESS) * 2.0;
+    let height = (WORLD_H + WORLD_BORDER_THICKNESS) * 2.0;
 
+    for (x, y, size) in [
+        (0.0, WORLD_H + half_thickness, vec2(width, WORLD_BORDER_THICKNESS)),
+        (0.0, -WORLD_H - half_thickness, vec2(width, WORLD_BORDER_THICKNESS)),
+        (WORLD_W + half_thickness, 0.0, vec2(WORLD_BORDER_THICKNESS, height)),
+        (-WORLD_W - half_thickness, 0.0, vec2(WORLD_BORDER_THICKNESS, height)),
+    ] {
         commands.spawn((
-            SpriteSheetBundle {
-            texture: handle.image.clone().unwrap(),
-            atlas: TextureAtlas {
-                layout: handle.layout.clone().unwrap(),
-                index: rng.gen_range(24..=25),
-            },
-            transform: Transform::from_translation(vec3(x, y, -1.))
-                .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-            ..default()
+            SpriteBundle {
+                sprite: Sprite {
+                    color: WORLD_BORDER_COLOR,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(vec3(x, y, -0.5)),
+                ..default()
             },
             GameEntity,
         ));
@@ -99,3 +115,46 @@ fn despawn_all_game_entities(
         commands.entity(e).despawn_recursive();
     }
 }
+
+/// A random point `min_distance..=max_distance` away from `center` that lies inside the world.
+/// Out-of-world samples are redrawn rather than clamped, since clamping can pull a far point
+/// right next to a `center` standing at the border.
+pub fn random_position_around(center: Vec2, min_distance: f32, max_distance: f32) -> Vec2 {
+    let mut rng = rand::thread_rng();
+    for _ in 0..SPAWN_POSITION_ATTEMPTS {
+        let angle = rng.gen_range(0.0..PI * 2.0);
+        let distance = rng.gen_range(min_distance..=max_distance);
+        let pos = center + Vec2::from_angle(angle) * distance;
+        if pos.x.abs() <= WORLD_W && pos.y.abs() <= WORLD_H {
+            return pos;
+        }
+    }
+
+    // Towards the middle of the world there is always room for the minimum distance.
+    center + (-center).try_normalize().unwrap_or(Vec2::X) * min_distance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_position_stays_in_the_world_at_the_border() {
+        let center = vec2(WORLD_W, WORLD_H);
+        for _ in 0..200 {
+            let pos = random_position_around(center, 500.0, 1000.0);
+            let distance = pos.distance(center);
+
+            assert!(pos.x.abs() <= WORLD_W && pos.y.abs() <= WORLD_H);
+            assert!((500.0 - 1e-2..=1000.0 + 1e-2).contains(&distance));
+        }
+    }
+
+    #[test]
+    fn random_position_respects_the_distance_range() {
+        for _ in 0..200 {
+            let distance = random_position_around(Vec2::ZERO, 300.0, 300.0).length();
+            assert!((distance - 300.0).abs() < 1e-2);
+        }
+    }
+}